@@ -37,6 +37,9 @@
             xorg.libXi
             xorg.libX11
 
+            # audio tracks
+            alsa-lib
+
           ];
 
           LD_LIBRARY_PATH = "${lib.makeLibraryPath buildInputs}";
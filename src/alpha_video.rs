@@ -0,0 +1,348 @@
+use std::{
+    cell::OnceCell,
+    collections::HashMap,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, SyncSender, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use egui::{ColorImage, TextureHandle};
+
+use crate::SlideOverlay;
+
+/// Number of frames decoded ahead of time
+const DECODE_AHEAD: usize = 4;
+/// Frame rate of videos not telling theirs
+const DEFAULT_FPS: f32 = 25.0;
+
+/// Video stream as reported by ffprobe.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlphaStream {
+    width: usize,
+    height: usize,
+    fps: f32,
+    /// Decoder to use instead of ffmpeg's own, which drops the alpha channel of VP8 / VP9
+    decoder: Option<&'static str>,
+    pub has_alpha: bool,
+}
+
+impl AlphaStream {
+    pub fn size(&self) -> egui::Vec2 {
+        egui::vec2(self.width as f32, self.height as f32)
+    }
+}
+
+fn pix_fmt_has_alpha(pix_fmt: &str) -> bool {
+    [
+        "yuva", "gbrap", "ya8", "ya16", "rgba", "bgra", "argb", "abgr",
+    ]
+    .iter()
+    .any(|prefix| pix_fmt.starts_with(prefix))
+}
+
+/// Parses the `key=value` lines of ffprobe, `None` without a video stream.
+fn parse_stream(output: &str) -> Option<AlphaStream> {
+    let values: HashMap<&str, &str> = output
+        .lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .collect();
+    // WebM stores the alpha channel of VP8 / VP9 beside the colours, flagged by `alpha_mode`
+    let decoder = match (values.get("codec_name"), values.get("TAG:alpha_mode")) {
+        (Some(&"vp9"), Some(&"1")) => Some("libvpx-vp9"),
+        (Some(&"vp8"), Some(&"1")) => Some("libvpx"),
+        _ => None,
+    };
+    let has_alpha = decoder.is_some()
+        || values
+            .get("pix_fmt")
+            .is_some_and(|pix_fmt| pix_fmt_has_alpha(pix_fmt));
+    let fps = values
+        .get("r_frame_rate")
+        .and_then(|rate| {
+            let (numer, denom) = rate.split_once('/')?;
+            Some(numer.parse::<f32>().ok()? / denom.parse::<f32>().ok()?)
+        })
+        .filter(|fps| fps.is_finite() && *fps > 0.0)
+        .unwrap_or(DEFAULT_FPS);
+    let dimension = |key| values.get(key)?.parse().ok().filter(|value| *value > 0);
+    Some(AlphaStream {
+        width: dimension("width")?,
+        height: dimension("height")?,
+        fps,
+        decoder,
+        has_alpha,
+    })
+}
+
+/// Reads the first video stream of the file with ffprobe.
+fn probe_stream(path: &Path) -> Result<AlphaStream, String> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0", "-show_entries"])
+        .arg("stream=codec_name,width,height,pix_fmt,r_frame_rate:stream_tags=alpha_mode")
+        .args(["-of", "default=noprint_wrappers=1"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("could not run ffprobe: {}", e))?;
+    parse_stream(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+        format!(
+            "`{}` has no video stream ffprobe could read",
+            path.to_string_lossy()
+        )
+    })
+}
+
+/// Spawns ffmpeg writing the frames of the video as raw RGBA to its stdout.
+fn spawn_decoder(path: &Path, stream: &AlphaStream) -> std::io::Result<std::process::Child> {
+    let mut command = Command::new("ffmpeg");
+    command.args(["-v", "error", "-nostdin"]);
+    if let Some(decoder) = stream.decoder {
+        command.args(["-c:v", decoder]);
+    }
+    command
+        .arg("-i")
+        .arg(path)
+        .args(["-map", "0:v:0", "-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+}
+
+/// Sends the decoded frames of the video over and over, `None` after each loop, until the
+/// receiver is gone.
+fn decode_frames(path: &Path, stream: &AlphaStream, frames_tx: SyncSender<Option<ColorImage>>) {
+    let frame_len = stream.width * stream.height * 4;
+    loop {
+        let mut decoder = match spawn_decoder(path, stream) {
+            Ok(decoder) => decoder,
+            Err(e) => {
+                println!(
+                    "Could not run ffmpeg for `{}`: {}",
+                    path.to_string_lossy(),
+                    e
+                );
+                return;
+            }
+        };
+        let Some(mut stdout) = decoder.stdout.take() else {
+            return;
+        };
+        let mut buffer = vec![0; frame_len];
+        let mut decoded_any = false;
+        let mut stopped = false;
+        while stdout.read_exact(&mut buffer).is_ok() {
+            decoded_any = true;
+            let image = ColorImage::from_rgba_unmultiplied([stream.width, stream.height], &buffer);
+            if frames_tx.send(Some(image)).is_err() {
+                stopped = true;
+                break;
+            }
+        }
+        let _ = decoder.kill();
+        let _ = decoder.wait();
+        // stopped by the player being destroyed, or no frame could be decoded at all
+        if stopped || !decoded_any || frames_tx.send(None).is_err() {
+            return;
+        }
+    }
+}
+
+pub struct PlayingAlphaVideo {
+    /// Stream of the video, probed in the background as well
+    stream_rx: Receiver<AlphaStream>,
+    /// Decoded frames, in order and looping; `None` marks the end of a loop
+    frames_rx: Receiver<Option<ColorImage>>,
+    texture: Option<TextureHandle>,
+    /// Number of frames shown since the start
+    frames_shown: u64,
+    /// Whether the end has been reached once
+    finished: bool,
+    started: Option<Instant>,
+    paused_at: Option<Duration>,
+}
+
+/// Plays videos with an alpha channel (e.g. VP9 in WebM or ProRes 4444), composited transparently.
+///
+/// The video player converts frames without their alpha channel, so these are probed by
+/// `ffprobe` and decoded to RGBA by `ffmpeg` in a background thread instead. They are played
+/// without sound.
+#[derive(Default)]
+pub struct AlphaVideoPlayer {
+    /// Known once probed, kept when destroyed
+    stream: OnceCell<AlphaStream>,
+    video: Option<PlayingAlphaVideo>,
+    /// Decoding failed, do not try again until destroyed
+    failed: bool,
+}
+
+impl AlphaVideoPlayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the video stream with ffprobe.
+    pub fn probe(path: &Path) -> Result<AlphaStream, String> {
+        probe_stream(path)
+    }
+
+    /// Stream of the video, `None` while it is still being probed.
+    fn stream(&self) -> Option<&AlphaStream> {
+        if self.stream.get().is_none() {
+            let stream = self.video.as_ref()?.stream_rx.try_recv().ok()?;
+            let _ = self.stream.set(stream);
+        }
+        self.stream.get()
+    }
+}
+
+impl SlideOverlay for AlphaVideoPlayer {
+    fn init(&mut self, ctx: &egui::Context, media_path: &str) {
+        if self.failed {
+            return;
+        }
+        let path = PathBuf::from(media_path);
+        let known_stream = self.stream.get().cloned();
+        let ctx = ctx.clone();
+        let (stream_tx, stream_rx) = mpsc::channel();
+        let (frames_tx, frames_rx) = mpsc::sync_channel(DECODE_AHEAD);
+        thread::spawn(move || {
+            let stream = match known_stream.map_or_else(|| probe_stream(&path), Ok) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    println!("Could not play `{}`: {}", path.to_string_lossy(), e);
+                    return;
+                }
+            };
+            if !stream.has_alpha {
+                println!(
+                    "`{}` has no alpha channel, play it with the type `video` instead",
+                    path.to_string_lossy()
+                );
+            }
+            if stream_tx.send(stream.clone()).is_err() {
+                return;
+            }
+            ctx.request_repaint();
+            decode_frames(&path, &stream, frames_tx);
+        });
+        self.video = Some(PlayingAlphaVideo {
+            stream_rx,
+            frames_rx,
+            texture: None,
+            frames_shown: 0,
+            finished: false,
+            started: None,
+            paused_at: None,
+        });
+    }
+
+    fn start(&mut self) {
+        if let Some(video) = self.video.as_mut() {
+            let paused_at = video.paused_at.take().unwrap_or_default();
+            video.started = Some(Instant::now() - paused_at);
+        }
+    }
+
+    fn render(&mut self, ui: &mut egui::Ui, rect: egui::Rect) -> Option<egui::Response> {
+        let fps = self.stream()?.fps;
+        let video = self.video.as_mut()?;
+        let elapsed = video.paused_at.unwrap_or_else(|| {
+            video
+                .started
+                .map(|started| started.elapsed())
+                .unwrap_or_default()
+        });
+        let due_frames = (elapsed.as_secs_f32() * fps) as u64 + 1;
+        // drops frames if decoding falls behind
+        let mut next_image = None;
+        while video.frames_shown < due_frames {
+            match video.frames_rx.try_recv() {
+                Ok(Some(image)) => {
+                    next_image = Some(image);
+                    video.frames_shown += 1;
+                }
+                Ok(None) => video.finished = true,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    println!("Could not decode the video with its alpha channel");
+                    self.video = None;
+                    self.failed = true;
+                    return None;
+                }
+            }
+        }
+        if let Some(image) = next_image {
+            match video.texture.as_mut() {
+                Some(texture) => texture.set(image, Default::default()),
+                None => {
+                    video.texture = Some(ui.ctx().load_texture(
+                        "alpha-video",
+                        image,
+                        Default::default(),
+                    ))
+                }
+            }
+        }
+        if video.paused_at.is_none() {
+            ui.ctx()
+                .request_repaint_after(Duration::from_secs_f32(1.0 / fps));
+        }
+        let texture = video.texture.as_ref()?;
+        Some(ui.put(
+            rect,
+            egui::Image::new((texture.id(), rect.size())).fit_to_exact_size(rect.size()),
+        ))
+    }
+
+    fn pause(&mut self) {
+        if let Some(video) = self.video.as_mut() {
+            let elapsed = video.started.map(|started| started.elapsed());
+            video.paused_at = video.paused_at.or(elapsed);
+        }
+    }
+
+    fn is_playing(&self) -> bool {
+        self.video.is_some()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.video.as_ref().is_some_and(|video| video.finished)
+    }
+
+    fn destroy(&mut self) {
+        self.video = None;
+        self.failed = false;
+    }
+
+    fn size(&self) -> Option<egui::Vec2> {
+        self.stream().map(AlphaStream::size)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_ffprobe_output() {
+        let webm = "codec_name=vp9\nwidth=1280\nheight=720\npix_fmt=yuv420p\nr_frame_rate=30000/1001\nTAG:alpha_mode=1\n";
+        let stream = parse_stream(webm).unwrap();
+        assert_eq!((stream.width, stream.height), (1280, 720));
+        assert!((stream.fps - 29.97).abs() < 0.01);
+        assert_eq!(stream.decoder, Some("libvpx-vp9"));
+        assert!(stream.has_alpha);
+
+        let prores =
+            "codec_name=prores\nwidth=1920\nheight=1080\npix_fmt=yuva444p10le\nr_frame_rate=25/1\n";
+        let stream = parse_stream(prores).unwrap();
+        assert_eq!((stream.decoder, stream.has_alpha), (None, true));
+
+        let opaque =
+            "codec_name=h264\nwidth=1920\nheight=1080\npix_fmt=yuv420p\nr_frame_rate=25/1\n";
+        assert!(!parse_stream(opaque).unwrap().has_alpha);
+        assert_eq!(parse_stream("codec_name=h264\n"), None);
+    }
+}
@@ -1,13 +1,74 @@
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 use egui::{ColorImage, TextureHandle};
 use pdfium_render::prelude::PdfRenderConfig;
 use std::sync::mpsc;
 
 use crate::{
+    audio::AudioPlayer,
+    auto_advance::{AutoAdvance, KIOSK_DURATION},
+    editor::Editor,
+    history::JumpHistory,
+    ink::{InkLayer, InkTool},
+    links::Links,
+    outline::Outline,
     pdf::PdfRenderer,
+    pointer::PointerTools,
+    search::Search,
     slides::{Slides, SlidesCache},
-    VideoEntry,
+    timer::TalkTimer,
+    transition::RunningTransition,
+    zoom::Zoom,
+    Config, Transitions,
+};
+
+/// Keys the viewer reacts to, which therefore cannot trigger animations
+pub(crate) const VIEWER_KEYS: [egui::Key; 39] = {
+    use egui::Key;
+    [
+        Key::ArrowRight,
+        Key::L,
+        Key::N,
+        Key::Space,
+        Key::PageDown,
+        Key::ArrowLeft,
+        Key::H,
+        Key::P,
+        Key::PageUp,
+        Key::G,
+        Key::Enter,
+        Key::O,
+        Key::I,
+        Key::CloseBracket,
+        Key::OpenBracket,
+        Key::Slash,
+        Key::F,
+        Key::F11,
+        Key::Escape,
+        Key::Backspace,
+        Key::U,
+        Key::X,
+        Key::C,
+        Key::Q,
+        Key::S,
+        Key::Z,
+        Key::T,
+        Key::M,
+        Key::E,
+        Key::Num0,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+    ]
 };
 
 fn is_num(key: &egui::Key) -> bool {
@@ -44,16 +105,75 @@ fn key_to_num(key: &egui::Key) -> Option<usize> {
     }
 }
 
+/// Options of the viewer given on the command line.
+#[derive(Clone, Debug, Default)]
+pub struct ViewerOptions {
+    /// Run unattended: no manual navigation and no cursor, advance and loop automatically
+    pub kiosk: bool,
+    /// File to record the time spent on each slide to
+    pub rehearsal_path: Option<PathBuf>,
+    /// Sidecar file keeping the ink annotations
+    pub ink_path: Option<PathBuf>,
+    /// Slide to start at
+    pub start_slide: usize,
+}
+
+/// Auto-advance of the configuration, in kiosk mode looping and with a default duration.
+fn auto_advance(config: &Config, kiosk: bool) -> Option<AutoAdvance> {
+    let auto_advance = config.auto_advance();
+    if !kiosk {
+        return auto_advance;
+    }
+    let auto_advance = auto_advance.unwrap_or_else(|| AutoAdvance {
+        duration: Some(KIOSK_DURATION),
+        after_videos: true,
+        ..Default::default()
+    });
+    Some(AutoAdvance {
+        looping: true,
+        ..auto_advance
+    })
+}
+
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 pub struct TemplateApp {
     // Example stuff:
     slides: SlidesCache,
     texture: TextureHandle,
-    config_changed_rx: Option<mpsc::Receiver<Vec<VideoEntry>>>,
+    /// Slide shown before the current one, kept for transitions
+    previous_texture: TextureHandle,
+    config_changed_rx: Option<mpsc::Receiver<Config>>,
 
     requested_page_idx: usize,
+    /// Slides jumped away from, to return there
+    history: JumpHistory,
+    /// Slide the texture has been rendered for
+    shown_page_idx: usize,
+
+    /// Since when the current slide is shown
+    shown_since: Instant,
+
+    transitions: Transitions,
+    transition: Option<RunningTransition>,
+
+    auto_advance: Option<AutoAdvance>,
+    /// Unattended: no manual navigation and no cursor
+    kiosk: bool,
+
+    timer: TalkTimer,
+    /// File to record the time spent on each slide to
+    rehearsal_path: Option<PathBuf>,
 
     key_stack: Vec<egui::Key>,
+
+    pointer_tools: PointerTools,
+    ink: InkLayer,
+    links: Links,
+    outline: Outline,
+    search: Search,
+    zoom: Zoom,
+    editor: Editor,
+    audio: AudioPlayer,
 }
 
 impl TemplateApp {
@@ -61,23 +181,95 @@ impl TemplateApp {
     pub fn new(
         cc: &eframe::CreationContext<'_>,
         pdf_path: PathBuf,
-        config: Vec<VideoEntry>,
-        config_changed_rx: Option<mpsc::Receiver<Vec<VideoEntry>>>,
+        config: Config,
+        config_path: Option<PathBuf>,
+        config_changed_rx: Option<mpsc::Receiver<Config>>,
+        options: ViewerOptions,
     ) -> Self {
+        let ViewerOptions {
+            kiosk,
+            rehearsal_path,
+            ink_path,
+            start_slide,
+        } = options;
         // This is also where you can customize the look and feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
         let pdf_renderer = PdfRenderer::new(PdfRenderConfig::new(), pdf_path);
+        let audio_entries = config.audio_entries();
+        let transitions = config.transitions();
+        let auto_advance = auto_advance(&config, kiosk);
+        let mut timer = TalkTimer::new(config.timer_settings());
+        timer.visible = rehearsal_path.is_some();
+        let pdf_media = config.pdf_media();
+        let slides = SlidesCache::new(
+            Slides::new(pdf_renderer),
+            100,
+            100,
+            config.video_entries(),
+            pdf_media,
+        );
+        let links = Links::new(slides.links());
+        let outline = Outline::new(slides.outline());
+        let start_slide = start_slide.min(slides.num_pages().saturating_sub(1));
+        timer.change_slide(start_slide);
+        let mut audio = AudioPlayer::new();
+        audio.change_audio_entries(audio_entries, |audio_path| {
+            slides.resolve_media_path(audio_path)
+        });
 
         Self {
-            slides: SlidesCache::new(Slides::new(pdf_renderer), 100, 100, config),
+            slides,
             texture: cc.egui_ctx.load_texture(
                 "slides_page",
                 ColorImage::example(),
                 Default::default(),
             ),
-            requested_page_idx: 0,
+            previous_texture: cc.egui_ctx.load_texture(
+                "previous_slides_page",
+                ColorImage::example(),
+                Default::default(),
+            ),
+            requested_page_idx: start_slide,
+            history: JumpHistory::default(),
+            shown_page_idx: start_slide,
+            shown_since: Instant::now(),
+            transitions,
+            transition: None,
+            auto_advance,
+            kiosk,
+            timer,
+            rehearsal_path,
             key_stack: Vec::new(),
+            pointer_tools: PointerTools::default(),
+            ink: InkLayer::new(ink_path),
+            links,
+            outline,
+            search: Search::default(),
+            zoom: Zoom::new(&cc.egui_ctx),
             config_changed_rx,
+            editor: Editor::new(config_path),
+            audio,
+        }
+    }
+
+    /// Records the time spent on each slide so far, if rehearsing.
+    fn write_rehearsal(&self) {
+        if let Some(rehearsal_path) = &self.rehearsal_path {
+            if let Err(e) = self
+                .timer
+                .write_rehearsal(rehearsal_path, self.slides.num_pages())
+            {
+                println!("Could not write the rehearsal: {}", e);
+            }
+        }
+    }
+
+    /// Jumps to the slide (or the last one), so that the history can return from it.
+    fn jump_to(&mut self, page_idx: usize) {
+        let page_idx = page_idx.min(self.slides.num_pages() - 1);
+        if page_idx != self.requested_page_idx {
+            self.history.jump(self.requested_page_idx);
+            self.requested_page_idx = page_idx;
         }
     }
 
@@ -96,10 +288,28 @@ impl TemplateApp {
 }
 
 impl eframe::App for TemplateApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.write_rehearsal();
+    }
+
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // before the central panel, which takes the rest of the window
+        if let Some(page_idx) = self.outline.ui(ctx, self.requested_page_idx) {
+            self.jump_to(page_idx);
+        }
         egui::CentralPanel::default().show(ctx, |ui| {
+            if self.kiosk {
+                ctx.set_cursor_icon(egui::CursorIcon::None);
+            }
+            // no manual navigation in kiosk mode, while asked to open a link or while typing
+            let keys_enabled =
+                !(self.kiosk || self.links.is_confirming() || ctx.wants_keyboard_input());
+            let mut fullscreen = None;
             ctx.input(|i| {
+                if !keys_enabled {
+                    return;
+                }
                 // println!("{:?}", i.keys_down);
                 // next slide
                 if (i.key_pressed(egui::Key::ArrowRight)
@@ -121,31 +331,101 @@ impl eframe::App for TemplateApp {
                 }
                 // jump to slide (or last slide)
                 if i.modifiers.shift_only() && i.key_pressed(egui::Key::G) {
-                    if let Some(num) = self.stack_as_num() {
-                        self.requested_page_idx = if num >= self.slides.num_pages() - 1 {
-                            self.slides.num_pages() - 1
-                        } else {
-                            num
-                        }
-                    } else {
-                        self.requested_page_idx = self.slides.num_pages() - 1
-                    }
+                    self.jump_to(self.stack_as_num().unwrap_or(usize::MAX));
                     self.key_stack.clear();
                 }
                 // jump to slide
                 if i.key_pressed(egui::Key::Enter) {
                     if let Some(num) = self.stack_as_num() {
-                        self.requested_page_idx = if num >= self.slides.num_pages() - 1 {
-                            self.slides.num_pages() - 1
-                        } else {
-                            num
-                        }
+                        self.jump_to(num);
                     }
                     self.key_stack.clear();
                 }
-                // cancel key stack
+                // back to where a jump came from (and forward again), also with the mouse buttons
+                let back = (i.modifiers.command && i.key_pressed(egui::Key::O))
+                    || i.pointer.button_pressed(egui::PointerButton::Extra1);
+                let forward = (i.modifiers.command && i.key_pressed(egui::Key::I))
+                    || i.pointer.button_pressed(egui::PointerButton::Extra2);
+                if back {
+                    if let Some(page_idx) = self.history.back(self.requested_page_idx) {
+                        self.requested_page_idx = page_idx;
+                    }
+                }
+                if forward {
+                    if let Some(page_idx) = self.history.forward(self.requested_page_idx) {
+                        self.requested_page_idx = page_idx;
+                    }
+                }
+                // next and previous section of the outline
+                let section = if i.key_pressed(egui::Key::CloseBracket) {
+                    self.outline.next_section(self.requested_page_idx)
+                } else if i.key_pressed(egui::Key::OpenBracket) {
+                    self.outline.previous_section(self.requested_page_idx)
+                } else {
+                    None
+                };
+                if let Some(page_idx) = section {
+                    self.jump_to(page_idx);
+                }
+                // search the slides
+                if i.key_pressed(egui::Key::Slash)
+                    || (i.modifiers.command && i.key_pressed(egui::Key::F))
+                {
+                    self.search.open();
+                }
+                // enter or leave fullscreen
+                if (i.key_pressed(egui::Key::F) && !i.modifiers.command)
+                    || i.key_pressed(egui::Key::F11)
+                {
+                    fullscreen = Some(!i.viewport().fullscreen.unwrap_or(false));
+                }
+                // show or hide the outline
+                if i.key_pressed(egui::Key::O) && !i.modifiers.command {
+                    self.outline.visible = !self.outline.visible;
+                }
+                // cancel key stack and pointer tools
                 if i.key_pressed(egui::Key::Escape) {
                     self.key_stack.clear();
+                    self.pointer_tools = PointerTools::default();
+                    self.ink.tool = None;
+                }
+                // show the whole slide again
+                if i.key_pressed(egui::Key::Backspace) {
+                    self.zoom.reset();
+                }
+                // ink: pen, highlighter and eraser, clear the slide and undo
+                if i.key_pressed(egui::Key::I) && !i.modifiers.command {
+                    self.ink.toggle_tool(InkTool::Pen);
+                }
+                if i.key_pressed(egui::Key::U) {
+                    self.ink.toggle_tool(InkTool::Highlighter);
+                }
+                if i.key_pressed(egui::Key::X) {
+                    self.ink.toggle_tool(InkTool::Eraser);
+                }
+                if i.key_pressed(egui::Key::C) && !i.modifiers.command {
+                    self.ink.clear(self.requested_page_idx);
+                }
+                if i.key_pressed(egui::Key::Z) && i.modifiers.command {
+                    self.ink.undo(self.requested_page_idx);
+                }
+                // laser pointer while held, toggle spotlight and zoom lens
+                self.pointer_tools.laser = i.key_down(egui::Key::Q);
+                if i.key_pressed(egui::Key::S) {
+                    self.pointer_tools.spotlight = !self.pointer_tools.spotlight;
+                }
+                if i.key_pressed(egui::Key::Z) && !i.modifiers.command {
+                    self.pointer_tools.lens = !self.pointer_tools.lens;
+                }
+                // show or hide the talk timer, restart it with shift
+                if i.modifiers.shift_only() && i.key_pressed(egui::Key::T) {
+                    self.timer.restart();
+                } else if i.key_pressed(egui::Key::T) {
+                    self.timer.visible = !self.timer.visible;
+                }
+                // toggle edit mode to place the videos
+                if i.key_pressed(egui::Key::E) {
+                    self.editor.active = !self.editor.active;
                 }
                 // number pressed
                 if i.key_pressed(egui::Key::Num0)
@@ -171,23 +451,50 @@ impl eframe::App for TemplateApp {
                     }
                 }
             });
+            if let Some(fullscreen) = fullscreen {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(fullscreen));
+            }
 
             // handle config changes (for the `config` command)
             if let Some(config_changed_rx) = &self.config_changed_rx {
-                if let Ok(new_video_config) = config_changed_rx.try_recv() {
+                if let Ok(new_config) = config_changed_rx.try_recv() {
                     println!("Config changed from UI");
-                    self.slides.change_video_entries(new_video_config);
+                    let slides = &self.slides;
+                    self.audio
+                        .change_audio_entries(new_config.audio_entries(), |audio_path| {
+                            slides.resolve_media_path(audio_path)
+                        });
+                    self.transitions = new_config.transitions();
+                    self.auto_advance = auto_advance(&new_config, self.kiosk);
+                    self.timer.settings = new_config.timer_settings();
+                    self.slides.change_video_entries(new_config.video_entries());
                 }
                 // necessary to register changes to the config
                 ctx.request_repaint();
             }
 
-            let size = ctx.input(|i: &egui::InputState| i.screen_rect());
-            let width = size.max.x;
-            let height = size.max.y;
+            // the window without the outline
+            let size = ctx.available_rect();
+            let width = size.width();
+            let height = size.height();
             self.slides.change_size(width as i32, height as i32);
 
             if let Some(img) = self.slides.get_page(self.requested_page_idx) {
+                if self.requested_page_idx != self.shown_page_idx {
+                    if let Some(transition) = self.transitions.to_slide(self.requested_page_idx) {
+                        // keep the outgoing slide around until the transition is done
+                        std::mem::swap(&mut self.texture, &mut self.previous_texture);
+                        self.transition = Some(RunningTransition::new(
+                            transition,
+                            self.requested_page_idx > self.shown_page_idx,
+                        ));
+                    }
+                    self.shown_page_idx = self.requested_page_idx;
+                    self.zoom.reset();
+                    self.shown_since = Instant::now();
+                    self.timer.change_slide(self.shown_page_idx);
+                    self.write_rehearsal();
+                }
                 self.texture.set(img, Default::default());
             }
 
@@ -196,17 +503,116 @@ impl eframe::App for TemplateApp {
             let available_rect = ui.available_rect_before_wrap();
             let slide_pos = available_rect.center() - 0.5 * slide_size;
             let img_rect = egui::Rect::from_min_size(slide_pos, slide_size);
-            ui.put(
-                img_rect,
-                egui::Image::new(sized_texture).fit_to_exact_size(slide_size),
-            );
-            self.slides
-                .handle_video(self.requested_page_idx, slide_pos, slide_size, ctx, ui);
+            if self
+                .transition
+                .as_ref()
+                .is_some_and(|transition| transition.is_done())
+            {
+                self.transition = None;
+            }
+            match &self.transition {
+                Some(transition) => {
+                    let (offset, opacity) = transition.paint(
+                        ui.painter(),
+                        &self.previous_texture,
+                        &self.texture,
+                        img_rect,
+                    );
+                    let ui_opacity = ui.opacity();
+                    ui.multiply_opacity(opacity);
+                    self.slides.handle_video(
+                        self.requested_page_idx,
+                        slide_pos + offset,
+                        slide_size,
+                        keys_enabled,
+                        ctx,
+                        ui,
+                    );
+                    ui.set_opacity(ui_opacity);
+                    ctx.request_repaint();
+                }
+                None => {
+                    if !self.kiosk {
+                        self.zoom.handle_input(ui, img_rect);
+                    }
+                    if self.zoom.is_zoomed() {
+                        self.zoom.paint(
+                            ui,
+                            &self.slides,
+                            self.requested_page_idx,
+                            &self.texture,
+                            img_rect,
+                        );
+                    } else {
+                        ui.put(
+                            img_rect,
+                            egui::Image::new(sized_texture).fit_to_exact_size(slide_size),
+                        );
+                    }
+                    // placed w.r.t. the whole slide, of which only the zoomed region is visible
+                    let slide_rect = self.zoom.slide_rect(img_rect);
+                    ui.scope(|ui| {
+                        ui.set_clip_rect(img_rect);
+                        self.slides.handle_video(
+                            self.requested_page_idx,
+                            slide_rect.min,
+                            slide_rect.size(),
+                            keys_enabled,
+                            ctx,
+                            ui,
+                        );
+                        self.search
+                            .paint_hits(ui, self.requested_page_idx, slide_rect);
+                        // drawing ink over links must not follow them
+                        if !self.kiosk && self.ink.tool.is_none() {
+                            if let Some(page_idx) =
+                                self.links.ui(ui, self.requested_page_idx, slide_rect)
+                            {
+                                self.jump_to(page_idx);
+                            }
+                        }
+                        self.ink.ui(ui, self.requested_page_idx, slide_rect);
+                    });
+                }
+            }
+            self.audio.update(self.requested_page_idx, ctx);
+            let slide_rect = self.zoom.slide_rect(img_rect);
+            self.pointer_tools.paint(ctx, &self.texture, slide_rect);
+            self.timer.ui(ctx, self.slides.num_pages());
+            if let Some(auto_advance) = &self.auto_advance {
+                if auto_advance.is_due(
+                    self.shown_page_idx,
+                    self.shown_since.elapsed(),
+                    self.slides.overlays_finished(self.shown_page_idx),
+                ) {
+                    if let Some(next_page_idx) =
+                        auto_advance.next_page(self.shown_page_idx, self.slides.num_pages())
+                    {
+                        self.requested_page_idx = next_page_idx;
+                        ctx.request_repaint();
+                    }
+                } else {
+                    // check again without waiting for input
+                    ctx.request_repaint_after(Duration::from_millis(100));
+                }
+            }
+            if self.editor.active {
+                self.editor.ui(
+                    ctx,
+                    ui,
+                    &mut self.slides,
+                    self.requested_page_idx,
+                    slide_rect,
+                );
+            }
 
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
                 // powered_by_egui_and_eframe(ui);
                 egui::warn_if_debug_build(ui);
             });
         });
+        if let Some(page_idx) = self.search.ui(ctx, &self.slides) {
+            self.jump_to(page_idx);
+        }
     }
 }
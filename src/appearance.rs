@@ -0,0 +1,330 @@
+use std::sync::Arc;
+
+use egui::{
+    emath::Rot2,
+    epaint::{Mesh, Shape},
+    layers::ShapeIdx,
+    Pos2, Rect, Shadow, Stroke, StrokeKind, Vec2,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{ChromaKey, SizeEntry};
+
+const FULL_UV: Rect = Rect::from_min_max(Pos2::ZERO, egui::pos2(1.0, 1.0));
+
+/// How the media fills its rect if both width and height are given.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Fit {
+    /// Scale to fit inside of the rect, keeping the aspect ratio
+    Contain,
+    /// Scale to cover the rect, keeping the aspect ratio and cutting off the rest
+    Cover,
+    /// Stretch to the rect
+    #[default]
+    Stretch,
+}
+
+impl Fit {
+    /// Rect to paint the media (of the given size) to and the part of it to show.
+    pub fn apply(&self, rect: Rect, media_size: Vec2, uv: Rect) -> (Rect, Rect) {
+        if media_size.x <= 0.0 || media_size.y <= 0.0 {
+            return (rect, uv);
+        }
+        let scale = rect.size() / media_size;
+        match self {
+            Fit::Stretch => (rect, uv),
+            Fit::Contain => (
+                Rect::from_center_size(rect.center(), media_size * scale.min_elem()),
+                uv,
+            ),
+            Fit::Cover => {
+                let visible = scale / scale.max_elem();
+                (
+                    rect,
+                    Rect::from_center_size(uv.center(), uv.size() * visible),
+                )
+            }
+        }
+    }
+}
+
+/// Parts of the media to cut off at each edge, e.g. black bars of a screen recording.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crop {
+    pub left: SizeEntry,
+    pub top: SizeEntry,
+    pub right: SizeEntry,
+    pub bottom: SizeEntry,
+}
+
+impl Default for Crop {
+    fn default() -> Self {
+        Self {
+            left: SizeEntry::Percent(0.0),
+            top: SizeEntry::Percent(0.0),
+            right: SizeEntry::Percent(0.0),
+            bottom: SizeEntry::Percent(0.0),
+        }
+    }
+}
+
+impl Crop {
+    fn unchecked_uv(&self) -> Rect {
+        Rect::from_min_max(
+            egui::pos2(self.left.calc_size(1.0), self.top.calc_size(1.0)),
+            egui::pos2(
+                1.0 - self.right.calc_size(1.0),
+                1.0 - self.bottom.calc_size(1.0),
+            ),
+        )
+    }
+
+    /// Whether no edge is negative and some of the media remains.
+    pub fn is_valid(&self) -> bool {
+        let uv = self.unchecked_uv();
+        FULL_UV.contains_rect(uv) && uv.width() > 0.0 && uv.height() > 0.0
+    }
+
+    /// Texture coordinates of the remaining part of the media, all of it for invalid crops.
+    pub fn uv(&self) -> Rect {
+        if self.is_valid() {
+            self.unchecked_uv()
+        } else {
+            FULL_UV
+        }
+    }
+}
+
+/// How an overlay is painted, independent of where it is placed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Appearance {
+    /// Overlays with a higher z-index are painted on top
+    pub z_index: i32,
+    pub opacity: f32,
+    /// Clockwise rotation in degrees around the centre
+    pub rotation: f32,
+    pub crop: Crop,
+    pub fit: Fit,
+    /// Radius of the corners in points, not applied to rotated overlays
+    pub corner_radius: f32,
+    pub border: Option<Stroke>,
+    pub shadow: Option<Shadow>,
+    /// Colour to leave out, corners are not rounded when set
+    pub chroma_key: Option<ChromaKey>,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            z_index: 0,
+            opacity: 1.0,
+            rotation: 0.0,
+            crop: Crop::default(),
+            fit: Fit::default(),
+            corner_radius: 0.0,
+            border: None,
+            shadow: None,
+            chroma_key: None,
+        }
+    }
+}
+
+impl Appearance {
+    /// Renders an overlay into `rect` with this appearance, showing the `uv` part of its texture.
+    ///
+    /// The overlay paints its texture into the rect as usual, cropping, rotation, rounding and
+    /// chroma keying are applied to the painted shapes afterwards. This way, overlays do not need to know about
+    /// them.
+    pub fn render<R>(
+        &self,
+        ui: &mut egui::Ui,
+        rect: Rect,
+        uv: Rect,
+        render: impl FnOnce(&mut egui::Ui, Rect) -> R,
+    ) -> R {
+        let opacity = ui.opacity();
+        ui.multiply_opacity(self.opacity);
+        let rotation = (self.rotation != 0.0).then(|| Rot2::from_angle(self.rotation.to_radians()));
+        let corners = rotation.map(|rotation| {
+            [
+                rect.left_top(),
+                rect.right_top(),
+                rect.right_bottom(),
+                rect.left_bottom(),
+            ]
+            .map(|corner| rect.center() + rotation * (corner - rect.center()))
+        });
+
+        // the shadow lies under the overlay
+        if let Some(shadow) = &self.shadow {
+            let shape = match corners {
+                None => shadow.as_shape(rect, self.corner_radius).into(),
+                // rotated rects cannot be blurred
+                Some(corners) => Shape::convex_polygon(
+                    corners
+                        .map(|corner| {
+                            corner + egui::vec2(shadow.offset[0] as f32, shadow.offset[1] as f32)
+                        })
+                        .to_vec(),
+                    shadow.color,
+                    Stroke::NONE,
+                ),
+            };
+            ui.painter().add(shape);
+        }
+
+        let layer_id = ui.layer_id();
+        let start = ui
+            .ctx()
+            .graphics_mut(|graphics| graphics.entry(layer_id).next_idx());
+        let result = render(ui, rect);
+        if rotation.is_some()
+            || uv != FULL_UV
+            || self.corner_radius > 0.0
+            || self.chroma_key.is_some()
+        {
+            ui.ctx().graphics_mut(|graphics| {
+                let paint_list = graphics.entry(layer_id);
+                for idx in start.0..paint_list.next_idx().0 {
+                    paint_list.mutate_shape(ShapeIdx(idx), |clipped_shape| {
+                        transform_shape(
+                            &mut clipped_shape.shape,
+                            uv,
+                            rotation.map(|rotation| (rotation, rect.center())),
+                            self.corner_radius,
+                            self.chroma_key.as_ref(),
+                        )
+                    });
+                }
+            });
+        }
+
+        if let Some(border) = self.border {
+            match corners {
+                None => {
+                    ui.painter()
+                        .rect_stroke(rect, self.corner_radius, border, StrokeKind::Outside);
+                }
+                Some(corners) => {
+                    ui.painter()
+                        .add(Shape::closed_line(corners.to_vec(), border));
+                }
+            }
+        }
+        ui.set_opacity(opacity);
+        result
+    }
+}
+
+/// Part `inner` of the texture coordinates `uv`.
+fn sub_uv(uv: Rect, inner: Rect) -> Rect {
+    Rect::from_min_max(
+        uv.min + inner.min.to_vec2() * uv.size(),
+        uv.min + inner.max.to_vec2() * uv.size(),
+    )
+}
+
+/// Shows the `uv` part of a textured shape, rounds its corners or rotates it around the origin
+/// and leaves out the colour of the chroma key.
+///
+/// Shapes without texture (e.g. controls) are left as is.
+fn transform_shape(
+    shape: &mut Shape,
+    uv: Rect,
+    rotation: Option<(Rot2, Pos2)>,
+    corner_radius: f32,
+    chroma_key: Option<&ChromaKey>,
+) {
+    match shape {
+        Shape::Rect(rect_shape) => {
+            let Some(brush) = rect_shape.brush.as_mut() else {
+                return;
+            };
+            let brush = Arc::make_mut(brush);
+            brush.uv = sub_uv(brush.uv, uv);
+            if rotation.is_none() && chroma_key.is_none() {
+                rect_shape.corner_radius = corner_radius.into();
+                return;
+            }
+            // textured rects can neither be rotated nor keyed, turn them into meshes
+            let mut mesh = Mesh::with_texture(brush.fill_texture_id);
+            mesh.add_rect_with_uv(rect_shape.rect, brush.uv, rect_shape.fill);
+            if let Some((rotation, origin)) = rotation {
+                mesh.rotate(rotation, origin);
+            }
+            *shape = match chroma_key {
+                Some(chroma_key) => chroma_key.shape(mesh),
+                None => Shape::mesh(mesh),
+            };
+        }
+        Shape::Mesh(mesh) if mesh.texture_id != egui::TextureId::default() => {
+            let mesh_mut = Arc::make_mut(mesh);
+            for vertex in mesh_mut.vertices.iter_mut() {
+                vertex.uv = uv.min + vertex.uv.to_vec2() * uv.size();
+            }
+            if let Some((rotation, origin)) = rotation {
+                mesh_mut.rotate(rotation, origin);
+            }
+            if let Some(chroma_key) = chroma_key {
+                *shape = chroma_key.shape(Arc::unwrap_or_clone(mesh.clone()));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fit_media_into_rect() {
+        let rect = Rect::from_min_size(Pos2::ZERO, egui::vec2(200.0, 100.0));
+        let media_size = egui::vec2(100.0, 100.0);
+
+        let (contained, uv) = Fit::Contain.apply(rect, media_size, FULL_UV);
+        assert_eq!(
+            contained,
+            Rect::from_min_size(egui::pos2(50.0, 0.0), egui::vec2(100.0, 100.0))
+        );
+        assert_eq!(uv, FULL_UV);
+
+        let (covered, uv) = Fit::Cover.apply(rect, media_size, FULL_UV);
+        assert_eq!(covered, rect);
+        assert_eq!(
+            uv,
+            Rect::from_min_max(egui::pos2(0.0, 0.25), egui::pos2(1.0, 0.75))
+        );
+    }
+
+    #[test]
+    fn crop_uv() {
+        let crop = Crop {
+            top: SizeEntry::Percent(10.0),
+            bottom: SizeEntry::Percent(10.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            crop.uv(),
+            Rect::from_min_max(egui::pos2(0.0, 0.1), egui::pos2(1.0, 0.9))
+        );
+
+        // nothing left, or more than all of it
+        for crop in [
+            Crop {
+                left: SizeEntry::Percent(60.0),
+                right: SizeEntry::Percent(40.0),
+                ..Default::default()
+            },
+            Crop {
+                top: SizeEntry::Percent(-10.0),
+                ..Default::default()
+            },
+        ] {
+            assert!(!crop.is_valid());
+            assert_eq!(crop.uv(), FULL_UV);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+
+use crate::AudioEntry;
+
+struct AudioTrack {
+    entry: AudioEntry,
+    /// Path to open the audio with (e.g. of an extracted attachment)
+    media_path: PathBuf,
+    sink: Option<Sink>,
+    /// Since when the track fades out
+    fading_since: Option<Instant>,
+    /// Opening failed, do not try again until the range is left
+    failed: bool,
+}
+
+impl AudioTrack {
+    fn open(&self, stream_handle: &OutputStreamHandle) -> Result<Sink, String> {
+        let file = File::open(&self.media_path)
+            .map_err(|e| format!("{}: {}", self.media_path.to_string_lossy(), e))?;
+        let source = Decoder::new(BufReader::new(file))
+            .map_err(|e| format!("{}: {}", self.media_path.to_string_lossy(), e))?;
+        let sink = Sink::try_new(stream_handle).map_err(|e| e.to_string())?;
+        if self.entry.looping {
+            sink.append(source.repeat_infinite());
+        } else {
+            sink.append(source);
+        }
+        sink.set_volume(self.entry.volume);
+        Ok(sink)
+    }
+
+    /// Starts, keeps or fades out the track for the given slide.
+    ///
+    /// Returns whether the track is fading out.
+    fn update(&mut self, page_idx: usize, stream_handle: &OutputStreamHandle) -> bool {
+        if self.entry.slide_range.contains(&page_idx) {
+            if let Some(sink) = &self.sink {
+                // back inside of the range while fading out
+                if self.fading_since.take().is_some() {
+                    sink.set_volume(self.entry.volume);
+                }
+            } else if !self.failed {
+                match self.open(stream_handle) {
+                    Ok(sink) => self.sink = Some(sink),
+                    Err(e) => {
+                        println!("Could not play audio: {}", e);
+                        self.failed = true;
+                    }
+                }
+            }
+            return false;
+        }
+
+        self.failed = false;
+        let Some(sink) = &self.sink else {
+            return false;
+        };
+        let fading_since = *self.fading_since.get_or_insert_with(Instant::now);
+        let fade_out = self.entry.fade_out.as_secs_f32();
+        let progress = if fade_out > 0.0 {
+            fading_since.elapsed().as_secs_f32() / fade_out
+        } else {
+            1.0
+        };
+        if progress >= 1.0 {
+            sink.stop();
+            self.sink = None;
+            self.fading_since = None;
+            return false;
+        }
+        sink.set_volume(self.entry.volume * (1.0 - progress));
+        true
+    }
+}
+
+/// Audio tracks spanning ranges of slides, independent of the videos of the slides.
+pub struct AudioPlayer {
+    /// Opened on demand, as long as no track is configured no audio device is needed
+    stream: Option<(OutputStream, OutputStreamHandle)>,
+    tracks: Vec<AudioTrack>,
+}
+
+impl AudioPlayer {
+    pub fn new() -> Self {
+        Self {
+            stream: None,
+            tracks: Vec::new(),
+        }
+    }
+
+    /// Replaces the tracks, tracks of unchanged entries keep playing.
+    ///
+    /// `resolve_media_path` gives the path to open an `audio_path` with.
+    pub fn change_audio_entries(
+        &mut self,
+        audio_entries: Vec<AudioEntry>,
+        resolve_media_path: impl Fn(&Path) -> PathBuf,
+    ) {
+        let mut old_tracks = std::mem::take(&mut self.tracks);
+        self.tracks = audio_entries
+            .into_iter()
+            .map(
+                |entry| match old_tracks.iter().position(|track| track.entry == entry) {
+                    Some(idx) => old_tracks.swap_remove(idx),
+                    None => AudioTrack {
+                        media_path: resolve_media_path(&entry.audio_path),
+                        entry,
+                        sink: None,
+                        fading_since: None,
+                        failed: false,
+                    },
+                },
+            )
+            .collect();
+    }
+
+    /// Plays the tracks of the given slide and fades out the others.
+    pub fn update(&mut self, page_idx: usize, ctx: &egui::Context) {
+        if self.tracks.is_empty() {
+            return;
+        }
+        if self.stream.is_none() {
+            match OutputStream::try_default() {
+                Ok(stream) => self.stream = Some(stream),
+                Err(e) => {
+                    println!("Could not open the audio device: {}", e);
+                    self.tracks.clear();
+                    return;
+                }
+            }
+        }
+        let Some((_, stream_handle)) = &self.stream else {
+            return;
+        };
+        let mut fading = false;
+        for track in self.tracks.iter_mut() {
+            fading |= track.update(page_idx, stream_handle);
+        }
+        if fading {
+            ctx.request_repaint_after(Duration::from_millis(30));
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::{collections::HashMap, time::Duration};
+
+/// Slide duration in kiosk mode if none is configured
+pub const KIOSK_DURATION: Duration = Duration::from_secs(10);
+
+/// Advances the slides without anyone at the keyboard, e.g. at a booth.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AutoAdvance {
+    /// How long slides without own duration are shown, `None` to wait for a key press
+    pub duration: Option<Duration>,
+    /// Durations of single slides by their index
+    pub slide_durations: HashMap<usize, Duration>,
+    /// Advance once all videos of a slide have played to their end instead
+    pub after_videos: bool,
+    /// Start over at the first slide after the last one
+    pub looping: bool,
+}
+
+impl AutoAdvance {
+    /// Whether to leave the slide after it has been shown for `shown_for`.
+    ///
+    /// `overlays_finished` tells whether all overlays of the slide have played to their end,
+    /// `None` if it has none.
+    pub fn is_due(
+        &self,
+        page_idx: usize,
+        shown_for: Duration,
+        overlays_finished: Option<bool>,
+    ) -> bool {
+        if self.after_videos {
+            if let Some(overlays_finished) = overlays_finished {
+                return overlays_finished;
+            }
+        }
+        self.slide_durations
+            .get(&page_idx)
+            .or(self.duration.as_ref())
+            .is_some_and(|duration| shown_for >= *duration)
+    }
+
+    /// Slide to advance to, `None` at the end of a deck which does not loop.
+    pub fn next_page(&self, page_idx: usize, num_pages: usize) -> Option<usize> {
+        if page_idx + 1 < num_pages {
+            Some(page_idx + 1)
+        } else if self.looping {
+            Some(0)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn advance_after_durations_and_videos() {
+        let auto_advance = AutoAdvance {
+            duration: Some(Duration::from_secs(10)),
+            slide_durations: HashMap::from([(1, Duration::from_secs(2))]),
+            after_videos: true,
+            looping: true,
+        };
+        assert!(!auto_advance.is_due(0, Duration::from_secs(5), None));
+        assert!(auto_advance.is_due(1, Duration::from_secs(5), None));
+        assert!(!auto_advance.is_due(1, Duration::from_secs(5), Some(false)));
+        assert!(auto_advance.is_due(0, Duration::ZERO, Some(true)));
+
+        assert_eq!(auto_advance.next_page(2, 4), Some(3));
+        assert_eq!(auto_advance.next_page(3, 4), Some(0));
+    }
+}
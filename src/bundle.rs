@@ -0,0 +1,270 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    env,
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+
+use pdfium_render::prelude::PdfRenderConfig;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    image_sequence::{frame_paths, is_image_sequence},
+    pdf::PdfRenderer,
+    Config, ATTACHMENT_PREFIX,
+};
+
+/// File extension of presentation bundles
+pub const BUNDLE_EXTENSION: &str = "bwb";
+
+/// Names of the files inside of the bundle
+const PDF_NAME: &str = "slides.pdf";
+const CONFIG_NAME: &str = "config.json";
+const MEDIA_DIR: &str = "media";
+
+/// A presentation bundle, extracted to disk.
+pub struct Bundle {
+    pub pdf_path: PathBuf,
+    /// Configuration with media paths pointing to the extracted media
+    pub config: Config,
+}
+
+pub fn is_bundle(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(BUNDLE_EXTENSION))
+}
+
+fn is_attachment(media_path: &Path) -> bool {
+    media_path
+        .to_str()
+        .is_some_and(|path| path.starts_with(ATTACHMENT_PREFIX))
+}
+
+fn add_file(
+    writer: &mut ZipWriter<File>,
+    name: &str,
+    path: &Path,
+    options: SimpleFileOptions,
+) -> Result<(), String> {
+    let mut file = File::open(path).map_err(|e| format!("{}: {}", path.to_string_lossy(), e))?;
+    writer
+        .start_file(name, options)
+        .map_err(|e| e.to_string())?;
+    io::copy(&mut file, writer).map_err(|e| format!("{}: {}", path.to_string_lossy(), e))?;
+    Ok(())
+}
+
+/// Adds a media file (or the frames of an image sequence) to the bundle, returns its path inside
+/// of the archive.
+fn add_media(
+    writer: &mut ZipWriter<File>,
+    media_path: &Path,
+    archive_paths: &HashMap<PathBuf, String>,
+    options: SimpleFileOptions,
+) -> Result<String, String> {
+    let sequence_pattern = (is_image_sequence(media_path) && !media_path.is_dir())
+        .then(|| media_path.file_name())
+        .flatten();
+    // the directory of an image sequence is packed as a whole
+    let packed_path = match sequence_pattern {
+        Some(_) => media_path.parent().unwrap_or(Path::new(".")),
+        None => media_path,
+    };
+    let file_name = fs::canonicalize(packed_path)
+        .unwrap_or_else(|_| packed_path.to_path_buf())
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut archive_path = format!("{}/{}", MEDIA_DIR, file_name);
+    // different files with the same name
+    if archive_paths
+        .values()
+        .any(|path| Path::new(path).starts_with(&archive_path))
+    {
+        archive_path = format!("{}/{}_{}", MEDIA_DIR, archive_paths.len(), file_name);
+    }
+    if is_image_sequence(media_path) {
+        for frame in frame_paths(media_path)? {
+            let frame_name = frame.file_name().unwrap_or_default().to_string_lossy();
+            add_file(
+                writer,
+                &format!("{}/{}", archive_path, frame_name),
+                &frame,
+                options,
+            )?;
+        }
+    } else {
+        add_file(writer, &archive_path, media_path, options)?;
+    }
+    if let Some(pattern) = sequence_pattern {
+        archive_path = format!("{}/{}", archive_path, pattern.to_string_lossy());
+    }
+    Ok(archive_path)
+}
+
+/// Packs the PDF, its configuration and all referenced media (including the frames of image
+/// sequences and the media named by annotations of the PDF) into a single bundle.
+///
+/// The media paths of the packed configuration point into the archive, the media of the PDF
+/// are redirected there by `pdf_media`. Attachments of the PDF are part of the PDF already and
+/// kept as is.
+pub fn pack(pdf_path: &Path, config: Config, output_path: &Path) -> Result<(), String> {
+    let pdf_renderer = PdfRenderer::try_new(PdfRenderConfig::new(), pdf_path.to_path_buf())
+        .map_err(|e| format!("{}: {}", pdf_path.to_string_lossy(), e))?;
+    let pdf_media = config.pdf_media();
+    let mut annotation_media: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for annotation in pdf_renderer.media_annotations() {
+        let is_packed = is_attachment(&annotation.named_path)
+            || pdf_media.contains_key(&annotation.named_path)
+            || annotation_media
+                .iter()
+                .any(|(named_path, _)| *named_path == annotation.named_path);
+        if !is_packed {
+            annotation_media.push((annotation.named_path, annotation.media_path));
+        }
+    }
+    write_bundle(pdf_path, config, annotation_media, output_path)
+}
+
+/// Writes the bundle of [`pack`], with the media named by annotations of the PDF given as pairs
+/// of the named path and the file.
+fn write_bundle(
+    pdf_path: &Path,
+    config: Config,
+    annotation_media: Vec<(PathBuf, PathBuf)>,
+    output_path: &Path,
+) -> Result<(), String> {
+    let file = File::create(output_path)
+        .map_err(|e| format!("{}: {}", output_path.to_string_lossy(), e))?;
+    let mut writer = ZipWriter::new(file);
+    // videos are compressed already
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(true);
+
+    add_file(&mut writer, PDF_NAME, pdf_path, options)?;
+
+    let mut archive_paths: HashMap<PathBuf, String> = HashMap::new();
+    let media_paths = config.packable_media_paths().into_iter().chain(
+        annotation_media
+            .iter()
+            .map(|(_, media_path)| media_path.clone()),
+    );
+    for media_path in media_paths {
+        if is_attachment(&media_path) || archive_paths.contains_key(&media_path) {
+            continue;
+        }
+        let archive_path = add_media(&mut writer, &media_path, &archive_paths, options)?;
+        archive_paths.insert(media_path, archive_path);
+    }
+
+    let mut config = config.map_media_paths(|media_path| match archive_paths.get(media_path) {
+        Some(archive_path) => PathBuf::from(archive_path),
+        None => media_path.to_path_buf(),
+    });
+    for (named_path, media_path) in annotation_media {
+        if let Some(archive_path) = archive_paths.get(&media_path) {
+            config = config.with_pdf_media(named_path, PathBuf::from(archive_path));
+        }
+    }
+    writer
+        .start_file(
+            CONFIG_NAME,
+            options.compression_method(CompressionMethod::Deflated),
+        )
+        .map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(&mut writer, &config).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Extracts the bundle to a temporary directory and loads its configuration.
+///
+/// The extraction is reused as long as the bundle does not change.
+pub fn unpack(bundle_path: &Path) -> Result<Bundle, String> {
+    let bundle_path = fs::canonicalize(bundle_path)
+        .map_err(|e| format!("{}: {}", bundle_path.to_string_lossy(), e))?;
+    let metadata = fs::metadata(&bundle_path).map_err(|e| e.to_string())?;
+    let mut hasher = DefaultHasher::new();
+    bundle_path.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    metadata.modified().ok().hash(&mut hasher);
+    let dir = env::temp_dir()
+        .join("bewegtbild")
+        .join(format!("bundle-{:016x}", hasher.finish()));
+
+    if !dir.exists() {
+        println!("Extracting bundle...");
+        // extract to a temporary directory first, so that an interrupted extraction is not reused
+        let partial_dir = dir.with_extension("part");
+        let _ = fs::remove_dir_all(&partial_dir);
+        let file = File::open(&bundle_path).map_err(|e| e.to_string())?;
+        ZipArchive::new(file)
+            .and_then(|mut archive| archive.extract(&partial_dir))
+            .map_err(|e| format!("{}: {}", bundle_path.to_string_lossy(), e))?;
+        fs::rename(&partial_dir, &dir).map_err(|e| e.to_string())?;
+    }
+
+    let config = match fs::read_to_string(dir.join(CONFIG_NAME)) {
+        Ok(config_str) => serde_json::from_str::<Config>(&config_str)
+            .map_err(|e| format!("{} of the bundle: {}", CONFIG_NAME, e))?,
+        Err(_) => Config::default(),
+    };
+    let config = config.map_media_paths(|media_path| {
+        if is_attachment(media_path) || media_path.is_absolute() {
+            media_path.to_path_buf()
+        } else {
+            dir.join(media_path)
+        }
+    });
+    Ok(Bundle {
+        pdf_path: dir.join(PDF_NAME),
+        config,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pack_custom_overlays() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdf_path = dir.path().join("talk.pdf");
+        let video_path = dir.path().join("clip.mp4");
+        fs::write(&pdf_path, b"%PDF-1.5").unwrap();
+        fs::write(&video_path, b"video").unwrap();
+        let config: Config = serde_json::from_str(&format!(
+            r#"{{"entries": [
+                {{"video_path": {:?}, "slide_num": 0, "size": "20%"}},
+                {{"video_path": "live:sensor", "type": "heatmap", "slide_num": 1, "size": "20%"}}
+            ]}}"#,
+            video_path.to_string_lossy()
+        ))
+        .unwrap();
+        let output_path = dir.path().join("talk.bwb");
+        write_bundle(&pdf_path, config, Vec::new(), &output_path).unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&output_path).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, vec![CONFIG_NAME, "media/clip.mp4", PDF_NAME]);
+        let config: Config =
+            serde_json::from_reader(archive.by_name(CONFIG_NAME).unwrap()).unwrap();
+        let video_paths: Vec<PathBuf> = config
+            .video_entries()
+            .into_iter()
+            .map(|entry| entry.video_path)
+            .collect();
+        // custom overlays are not backed by a file and keep their path
+        assert_eq!(
+            video_paths,
+            vec![
+                PathBuf::from("media/clip.mp4"),
+                PathBuf::from("live:sensor")
+            ]
+        );
+    }
+}
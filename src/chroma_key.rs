@@ -0,0 +1,263 @@
+use std::sync::{Arc, Mutex};
+
+use eframe::{
+    egui_glow::{self, ShaderVersion},
+    glow::{self, HasContext},
+};
+use egui::{epaint::Mesh, Color32, PaintCallback, PaintCallbackInfo, Shape};
+
+/// Makes pixels close to a colour (e.g. of a green screen) transparent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChromaKey {
+    pub color: Color32,
+    /// Distance (between 0 and 1) to the key colour up to which pixels are fully transparent
+    pub tolerance: f32,
+    /// Distance over which pixels fade in beyond the tolerance, to smooth the edges
+    pub softness: f32,
+}
+
+impl ChromaKey {
+    /// Paints the textured mesh with its key colour left out.
+    pub fn shape(&self, mesh: Mesh) -> Shape {
+        let chroma_key = *self;
+        Shape::Callback(PaintCallback {
+            rect: mesh.calc_bounds(),
+            callback: Arc::new(egui_glow::CallbackFn::new(move |info, painter| {
+                // SAFETY: the state changed here is restored by egui_glow after the callback
+                unsafe { paint(&chroma_key, &mesh, &info, painter) }
+            })),
+        })
+    }
+}
+
+const VERTEX_SHADER: &str = r#"
+#if NEW_SHADER_INTERFACE
+    #define I in
+    #define O out
+#else
+    #define I attribute
+    #define O varying
+#endif
+
+#ifdef GL_ES
+    precision mediump float;
+#endif
+
+I vec2 a_pos;
+I vec2 a_tc;
+I vec4 a_rgba;
+O vec2 v_tc;
+O vec4 v_rgba;
+
+void main() {
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+    v_tc = a_tc;
+    v_rgba = a_rgba;
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"
+#ifdef GL_ES
+    precision mediump float;
+#endif
+
+uniform sampler2D u_sampler;
+uniform vec3 u_key;
+uniform float u_tolerance;
+uniform float u_softness;
+
+#if NEW_SHADER_INTERFACE
+    in vec2 v_tc;
+    in vec4 v_rgba;
+    out vec4 f_color;
+    #define gl_FragColor f_color
+    #define texture2D texture
+#else
+    varying vec2 v_tc;
+    varying vec4 v_rgba;
+#endif
+
+vec3 srgb_gamma_from_linear(vec3 rgb) {
+    bvec3 cutoff = lessThan(rgb, vec3(0.0031308));
+    vec3 lower = rgb * vec3(12.92);
+    vec3 higher = vec3(1.055) * pow(rgb, vec3(1.0 / 2.4)) - vec3(0.055);
+    return mix(higher, lower, vec3(cutoff));
+}
+
+void main() {
+    vec4 texture_in_gamma = texture2D(u_sampler, v_tc);
+#if SRGB_TEXTURES
+    texture_in_gamma.rgb = srgb_gamma_from_linear(texture_in_gamma.rgb);
+#endif
+    // distance in the RGB cube, scaled to 0..1
+    float distance_to_key = distance(texture_in_gamma.rgb, u_key) / sqrt(3.0);
+    float mask = smoothstep(u_tolerance, u_tolerance + u_softness, distance_to_key);
+    // colours are premultiplied by their alpha
+    gl_FragColor = v_rgba * texture_in_gamma * mask;
+}
+"#;
+
+/// Shader program, shared by all chroma keyed overlays.
+struct Program {
+    program: glow::Program,
+    vertex_array: Option<glow::VertexArray>,
+    vertex_buffer: glow::Buffer,
+}
+
+static PROGRAM: Mutex<Option<Program>> = Mutex::new(None);
+
+unsafe fn compile_shader(
+    gl: &glow::Context,
+    kind: u32,
+    source: &str,
+) -> Result<glow::Shader, String> {
+    let shader = gl.create_shader(kind)?;
+    gl.shader_source(shader, source);
+    gl.compile_shader(shader);
+    if !gl.get_shader_compile_status(shader) {
+        return Err(gl.get_shader_info_log(shader));
+    }
+    Ok(shader)
+}
+
+unsafe fn create_program(gl: &glow::Context) -> Result<Program, String> {
+    let shader_version = ShaderVersion::get(gl);
+    // same as egui_glow, to sample the textures the same way
+    let srgb_textures = shader_version == ShaderVersion::Es300
+        || gl
+            .supported_extensions()
+            .iter()
+            .any(|extension| extension.contains("sRGB"));
+    let header = format!(
+        "{}\n#define NEW_SHADER_INTERFACE {}\n#define SRGB_TEXTURES {}\n",
+        shader_version.version_declaration(),
+        shader_version.is_new_shader_interface() as i32,
+        srgb_textures as i32
+    );
+    let program = gl.create_program()?;
+    let vertex_shader = compile_shader(
+        gl,
+        glow::VERTEX_SHADER,
+        &format!("{}{}", header, VERTEX_SHADER),
+    )?;
+    let fragment_shader = compile_shader(
+        gl,
+        glow::FRAGMENT_SHADER,
+        &format!("{}{}", header, FRAGMENT_SHADER),
+    )?;
+    gl.attach_shader(program, vertex_shader);
+    gl.attach_shader(program, fragment_shader);
+    gl.bind_attrib_location(program, 0, "a_pos");
+    gl.bind_attrib_location(program, 1, "a_tc");
+    gl.bind_attrib_location(program, 2, "a_rgba");
+    gl.link_program(program);
+    gl.detach_shader(program, vertex_shader);
+    gl.detach_shader(program, fragment_shader);
+    gl.delete_shader(vertex_shader);
+    gl.delete_shader(fragment_shader);
+    if !gl.get_program_link_status(program) {
+        return Err(gl.get_program_info_log(program));
+    }
+    Ok(Program {
+        program,
+        // not supported by every OpenGL version, but not needed either
+        vertex_array: gl.create_vertex_array().ok(),
+        vertex_buffer: gl.create_buffer()?,
+    })
+}
+
+unsafe fn paint(
+    chroma_key: &ChromaKey,
+    mesh: &Mesh,
+    info: &PaintCallbackInfo,
+    painter: &egui_glow::Painter,
+) {
+    let Some(texture) = painter.texture(mesh.texture_id) else {
+        return;
+    };
+    let gl = painter.gl();
+    let mut program = PROGRAM.lock().unwrap();
+    if program.is_none() {
+        match create_program(gl) {
+            Ok(created) => *program = Some(created),
+            Err(e) => {
+                // falls back to not painting the overlay at all
+                println!("Could not create the chroma key shader: {}", e);
+                return;
+            }
+        }
+    }
+    let Some(program) = program.as_ref() else {
+        return;
+    };
+
+    // positions relative to the viewport, which egui_glow set to the rect of the callback
+    let viewport = info.viewport;
+    let vertex_data: Vec<u8> = mesh
+        .indices
+        .iter()
+        .map(|idx| &mesh.vertices[*idx as usize])
+        .flat_map(|vertex| {
+            let [r, g, b, a] = vertex.color.to_array();
+            [
+                2.0 * (vertex.pos.x - viewport.left()) / viewport.width() - 1.0,
+                1.0 - 2.0 * (vertex.pos.y - viewport.top()) / viewport.height(),
+                vertex.uv.x,
+                vertex.uv.y,
+                r as f32 / 255.0,
+                g as f32 / 255.0,
+                b as f32 / 255.0,
+                a as f32 / 255.0,
+            ]
+        })
+        .flat_map(f32::to_ne_bytes)
+        .collect();
+
+    gl.use_program(Some(program.program));
+    let [r, g, b, _] = chroma_key.color.to_normalized_gamma_f32();
+    gl.uniform_3_f32(
+        gl.get_uniform_location(program.program, "u_key").as_ref(),
+        r,
+        g,
+        b,
+    );
+    gl.uniform_1_f32(
+        gl.get_uniform_location(program.program, "u_tolerance")
+            .as_ref(),
+        chroma_key.tolerance,
+    );
+    gl.uniform_1_f32(
+        gl.get_uniform_location(program.program, "u_softness")
+            .as_ref(),
+        chroma_key.softness.max(0.001),
+    );
+    gl.uniform_1_i32(
+        gl.get_uniform_location(program.program, "u_sampler")
+            .as_ref(),
+        0,
+    );
+    gl.active_texture(glow::TEXTURE0);
+    gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+
+    gl.bind_vertex_array(program.vertex_array);
+    gl.bind_buffer(glow::ARRAY_BUFFER, Some(program.vertex_buffer));
+    gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &vertex_data, glow::STREAM_DRAW);
+    let stride = 8 * std::mem::size_of::<f32>() as i32;
+    for (location, size, offset) in [(0, 2, 0), (1, 2, 2), (2, 4, 4)] {
+        gl.enable_vertex_attrib_array(location);
+        gl.vertex_attrib_pointer_f32(
+            location,
+            size,
+            glow::FLOAT,
+            false,
+            stride,
+            offset * std::mem::size_of::<f32>() as i32,
+        );
+    }
+    gl.draw_arrays(glow::TRIANGLES, 0, mesh.indices.len() as i32);
+    for location in 0..3 {
+        gl.disable_vertex_attrib_array(location);
+    }
+    gl.bind_vertex_array(None);
+    gl.bind_buffer(glow::ARRAY_BUFFER, None);
+}
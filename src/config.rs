@@ -1,11 +1,28 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+use egui::{Color32, Shadow, Stroke};
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, RootSchema, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{PosRequest, SizeEntry, SizeRequest, VideoEntry};
+use crate::{
+    Animation, Appearance, AudioEntry, AutoAdvance, ChromaKey, Crop, Easing, Fit, Keyframe,
+    MediaType, PosRequest, SizeEntry, SizeRequest, TimerSettings, Transition, TransitionKind,
+    Transitions, VideoEntry,
+};
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(untagged)]
+#[schemars(
+    description = "Width of the video (height follows its aspect ratio), or `[width, height]`"
+)]
 /// TODO: Questionalable configuration.
 /// Maybe explicit `width` and `height` could be preferred...
 ///   ```yaml
@@ -28,9 +45,23 @@ impl SizeRequestConfig {
             SizeRequestConfig::WidthAndHeight(w, h) => SizeRequest::Size(w, h),
         }
     }
+
+    /// Keeps the notation of `self` where possible.
+    fn with_size_request(self, size: SizeRequest) -> Self {
+        match (self, size) {
+            (SizeRequestConfig::WidthTuple(_), SizeRequest::AutoHeight(w)) => {
+                SizeRequestConfig::WidthTuple((w,))
+            }
+            (_, SizeRequest::AutoHeight(w)) => SizeRequestConfig::Width(w),
+            (_, SizeRequest::Size(w, h)) => SizeRequestConfig::WidthAndHeight(w, h),
+            // cannot be expressed in the configuration
+            (size_config, SizeRequest::AutoWidth(_)) => size_config,
+        }
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+/// Position `[x, y]` of the top left corner of the video on the slide.
 struct PosRequestConfig(SizeEntry, SizeEntry);
 
 impl Default for PosRequestConfig {
@@ -39,8 +70,201 @@ impl Default for PosRequestConfig {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
+/// Parts `[left, top, right, bottom]` of the video to cut off, e.g. black bars.
+struct CropConfig(SizeEntry, SizeEntry, SizeEntry, SizeEntry);
+
+impl CropConfig {
+    fn as_crop(&self) -> Crop {
+        Crop {
+            left: self.0,
+            top: self.1,
+            right: self.2,
+            bottom: self.3,
+        }
+    }
+
+    fn from_crop(crop: &Crop) -> Self {
+        CropConfig(crop.left, crop.top, crop.right, crop.bottom)
+    }
+}
+
+/// Colour as hex string, e.g. `#ff0000` or `#00000080` (with alpha).
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ColorConfig(Color32);
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
+/// Border around the video.
+struct BorderConfig {
+    /// Width in points
+    width: f32,
+    color: ColorConfig,
+}
+
+impl BorderConfig {
+    fn as_stroke(&self) -> Stroke {
+        Stroke::new(self.width, self.color.0)
+    }
+}
+
+fn default_shadow_offset() -> [f32; 2] {
+    [4.0, 4.0]
+}
+
+fn default_shadow_blur() -> f32 {
+    16.0
+}
+
+fn default_shadow_color() -> ColorConfig {
+    ColorConfig(Color32::from_black_alpha(128))
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
+/// Drop shadow under the video.
+struct ShadowConfig {
+    /// Offset `[x, y]` in points
+    #[serde(default = "default_shadow_offset")]
+    offset: [f32; 2],
+    /// Width of the blurred edge in points
+    #[serde(default = "default_shadow_blur")]
+    blur: f32,
+    /// Growth in all directions in points
+    #[serde(default)]
+    spread: f32,
+    #[serde(default = "default_shadow_color")]
+    color: ColorConfig,
+}
+
+impl ShadowConfig {
+    fn as_shadow(&self) -> Shadow {
+        Shadow {
+            offset: self
+                .offset
+                .map(|offset| offset.round().clamp(-128.0, 127.0) as i8),
+            blur: self.blur.round().clamp(0.0, 255.0) as u8,
+            spread: self.spread.round().clamp(0.0, 255.0) as u8,
+            color: self.color.0,
+        }
+    }
+}
+
+fn default_tolerance() -> f32 {
+    0.3
+}
+
+fn default_softness() -> f32 {
+    0.1
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
+/// Colour of the video to make transparent, e.g. of a green screen.
+struct ChromaKeyConfig {
+    color: ColorConfig,
+    /// Distance between 0 and 1 to the colour up to which pixels are transparent
+    #[serde(default = "default_tolerance")]
+    tolerance: f32,
+    /// Distance over which pixels fade in beyond the tolerance
+    #[serde(default = "default_softness")]
+    softness: f32,
+}
+
+impl ChromaKeyConfig {
+    fn as_chroma_key(&self) -> ChromaKey {
+        ChromaKey {
+            color: self.color.0,
+            tolerance: self.tolerance.clamp(0.0, 1.0),
+            softness: self.softness.max(0.0),
+        }
+    }
+}
+
+/// Name of a key, e.g. `Z` or `F5`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct KeyConfig(egui::Key);
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+/// Values of the video at a point in time, missing ones are taken from the entry.
+struct KeyframeConfig {
+    /// Seconds since the start of the animation
+    time: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pos: Option<PosRequestConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<SizeRequestConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    opacity: Option<f32>,
+    /// Easing of the transition towards this keyframe (defaults to linear)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    easing: Option<Easing>,
+}
+
+impl KeyframeConfig {
+    fn as_keyframe(&self) -> Keyframe {
+        Keyframe {
+            time: self.time.max(0.0),
+            pos: self.pos.as_ref().map(|pos| PosRequest {
+                width: pos.0,
+                height: pos.1,
+            }),
+            size: self.size.map(SizeRequestConfig::as_size_request),
+            opacity: self.opacity.map(|opacity| opacity.clamp(0.0, 1.0)),
+            easing: self.easing.unwrap_or_default(),
+        }
+    }
+
+    fn from_keyframe(keyframe: &Keyframe) -> Self {
+        Self {
+            time: keyframe.time,
+            pos: keyframe
+                .pos
+                .map(|pos| PosRequestConfig(pos.width, pos.height)),
+            size: keyframe.size.map(|size| {
+                SizeRequestConfig::Width(SizeEntry::Percent(30.0)).with_size_request(size)
+            }),
+            opacity: keyframe.opacity,
+            easing: Some(keyframe.easing).filter(|easing| *easing != Easing::default()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+/// Animation of the video along keyframes, e.g. to fly in or fade out.
+struct AnimationConfig {
+    /// Key starting the animation, by default it starts when the slide is shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trigger: Option<KeyConfig>,
+    keyframes: Vec<KeyframeConfig>,
+}
+
+impl AnimationConfig {
+    fn as_animation(&self) -> Animation {
+        let mut keyframes: Vec<Keyframe> = self
+            .keyframes
+            .iter()
+            .map(KeyframeConfig::as_keyframe)
+            .collect();
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Animation {
+            trigger: self.trigger.map(|trigger| trigger.0),
+            keyframes,
+        }
+    }
+
+    fn from_animation(animation: &Animation) -> Self {
+        Self {
+            trigger: animation.trigger.map(KeyConfig),
+            keyframes: animation
+                .keyframes
+                .iter()
+                .map(KeyframeConfig::from_keyframe)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
+/// Index of the slide (starting at 0) or a list of them.
 enum SlideNumConfig {
     Single(usize),
     Many(Vec<usize>),
@@ -55,36 +279,282 @@ impl SlideNumConfig {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 struct VideoConfig {
     #[serde(rename = "slide_num")]
     slide_nums: SlideNumConfig,
     video_path: PathBuf,
+    #[serde(default, rename = "type")]
+    media_type: MediaType,
+    /// Frame rate of an image sequence (defaults to 25)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fps: Option<f32>,
     #[serde(default)]
     pos: PosRequestConfig,
     size: SizeRequestConfig,
+    /// Videos with a higher z-index are painted on top (defaults to 0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    z_index: Option<i32>,
+    /// Opacity between 0 and 1 (defaults to 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    opacity: Option<f32>,
+    /// Clockwise rotation in degrees
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotation: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    crop: Option<CropConfig>,
+    /// How the video fills its size if both width and height are given (defaults to stretch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fit: Option<Fit>,
+    /// Radius of the corners in points
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    corner_radius: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    border: Option<BorderConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shadow: Option<ShadowConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chroma_key: Option<ChromaKeyConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    animation: Option<AnimationConfig>,
+}
+
+impl VideoConfig {
+    fn from_video_entry(entry: &VideoEntry) -> Self {
+        let appearance = &entry.appearance;
+        let defaults = Appearance::default();
+        Self {
+            slide_nums: match entry.slide_nums.as_slice() {
+                [num] => SlideNumConfig::Single(*num),
+                nums => SlideNumConfig::Many(nums.to_vec()),
+            },
+            video_path: entry.video_path.clone(),
+            media_type: entry.media_type.clone(),
+            fps: entry.fps,
+            pos: PosRequestConfig(entry.pos.width, entry.pos.height),
+            size: SizeRequestConfig::Width(SizeEntry::Percent(30.0)).with_size_request(entry.size),
+            // only what differs from the defaults
+            z_index: Some(appearance.z_index).filter(|z_index| *z_index != defaults.z_index),
+            opacity: Some(appearance.opacity).filter(|opacity| *opacity != defaults.opacity),
+            rotation: Some(appearance.rotation).filter(|rotation| *rotation != defaults.rotation),
+            crop: Some(CropConfig::from_crop(&appearance.crop))
+                .filter(|_| appearance.crop != defaults.crop),
+            fit: Some(appearance.fit).filter(|fit| *fit != defaults.fit),
+            corner_radius: Some(appearance.corner_radius)
+                .filter(|corner_radius| *corner_radius != defaults.corner_radius),
+            border: appearance.border.map(|border| BorderConfig {
+                width: border.width,
+                color: ColorConfig(border.color),
+            }),
+            shadow: appearance.shadow.map(|shadow| ShadowConfig {
+                offset: shadow.offset.map(f32::from),
+                blur: shadow.blur.into(),
+                spread: shadow.spread.into(),
+                color: ColorConfig(shadow.color),
+            }),
+            chroma_key: appearance.chroma_key.map(|chroma_key| ChromaKeyConfig {
+                color: ColorConfig(chroma_key.color),
+                tolerance: chroma_key.tolerance,
+                softness: chroma_key.softness,
+            }),
+            animation: entry
+                .animation
+                .as_ref()
+                .map(AnimationConfig::from_animation),
+        }
+    }
+
+    fn as_video_entry(&self) -> VideoEntry {
+        VideoEntry {
+            slide_nums: self.slide_nums.as_vec(),
+            video_path: self.video_path.clone(),
+            media_type: self.media_type.clone(),
+            fps: self.fps,
+            pos: PosRequest {
+                width: self.pos.0,
+                height: self.pos.1,
+            },
+            size: self.size.as_size_request(),
+            appearance: Appearance {
+                z_index: self.z_index.unwrap_or_default(),
+                opacity: self.opacity.unwrap_or(1.0).clamp(0.0, 1.0),
+                rotation: self.rotation.unwrap_or_default(),
+                crop: self
+                    .crop
+                    .as_ref()
+                    .map(CropConfig::as_crop)
+                    .unwrap_or_default(),
+                fit: self.fit.unwrap_or_default(),
+                corner_radius: self.corner_radius.unwrap_or_default().max(0.0),
+                border: self.border.as_ref().map(BorderConfig::as_stroke),
+                shadow: self.shadow.as_ref().map(ShadowConfig::as_shadow),
+                chroma_key: self.chroma_key.as_ref().map(ChromaKeyConfig::as_chroma_key),
+            },
+            animation: self.animation.as_ref().map(AnimationConfig::as_animation),
+        }
+    }
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+/// Duration of the given seconds, `None` if negative, not a number or too long.
+fn seconds(seconds: f32) -> Option<Duration> {
+    Duration::try_from_secs_f32(seconds).ok()
+}
+
+fn default_fade_out() -> f32 {
+    1.0
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+/// Audio track (e.g. background music or narration) spanning a range of slides.
+struct AudioConfig {
+    audio_path: PathBuf,
+    /// First and last slide (starting at 0) on which the track plays
+    slide_range: (usize, usize),
+    /// Volume between 0 and 1
+    #[serde(default = "default_volume")]
+    volume: f32,
+    /// Seconds to fade out once the range is left
+    #[serde(default = "default_fade_out")]
+    fade_out: f32,
+    /// Start over at the end of the track
+    #[serde(default, rename = "loop")]
+    looping: bool,
+}
+
+impl AudioConfig {
+    /// Does not fade out if `fade_out` is invalid, see [`Config::invalid_durations`].
+    fn as_audio_entry(&self) -> AudioEntry {
+        AudioEntry {
+            audio_path: self.audio_path.clone(),
+            slide_range: self.slide_range.0..=self.slide_range.1,
+            volume: self.volume.clamp(0.0, 1.0),
+            fade_out: seconds(self.fade_out).unwrap_or_default(),
+            looping: self.looping,
+        }
+    }
+}
+
+fn default_transition_duration() -> f32 {
+    0.5
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
+/// Transition to a slide, e.g. `{"type": "fade", "duration": 0.5}`.
+struct TransitionConfig {
+    #[serde(rename = "type")]
+    kind: TransitionKind,
+    /// Seconds (defaults to 0.5)
+    #[serde(default = "default_transition_duration")]
+    duration: f32,
+}
+
+impl TransitionConfig {
+    /// Switches instantly if `duration` is invalid, see [`Config::invalid_durations`].
+    fn as_transition(&self) -> Transition {
+        Transition {
+            kind: self.kind,
+            duration: seconds(self.duration).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+/// Advancing the slides without anyone at the keyboard.
+struct AutoAdvanceConfig {
+    /// Seconds each slide without own duration is shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<f32>,
+    /// Seconds single slides are shown, by their index
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    slide_durations: HashMap<usize, f32>,
+    /// Advance once all videos of a slide have played to their end
+    #[serde(default)]
+    after_videos: bool,
+    /// Start over at the first slide after the last one
+    #[serde(default, rename = "loop")]
+    looping: bool,
+}
+
+impl AutoAdvanceConfig {
+    /// Skips invalid durations, see [`Config::invalid_durations`].
+    fn as_auto_advance(&self) -> AutoAdvance {
+        AutoAdvance {
+            duration: self.duration.and_then(seconds),
+            slide_durations: self
+                .slide_durations
+                .iter()
+                .filter_map(|(page_idx, duration)| Some((*page_idx, seconds(*duration)?)))
+                .collect(),
+            after_videos: self.after_videos,
+            looping: self.looping,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+/// Target duration of the talk and time budgets of its slides, shown by the timer.
+struct TimerConfig {
+    /// Seconds the talk should take
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<f32>,
+    /// Seconds for single slides by their index, the others share the rest of `duration`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    slide_budgets: HashMap<usize, f32>,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+impl TimerConfig {
+    /// Skips invalid durations, see [`Config::invalid_durations`].
+    fn as_timer_settings(&self) -> TimerSettings {
+        TimerSettings {
+            duration: self.duration.and_then(seconds),
+            slide_budgets: self
+                .slide_budgets
+                .iter()
+                .filter_map(|(page_idx, budget)| Some((*page_idx, seconds(*budget)?)))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     pub entries: Vec<VideoConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    audio: Vec<AudioConfig>,
+    /// Transition to every slide
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transition: Option<TransitionConfig>,
+    /// Transitions to single slides by their index, overriding `transition`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    slide_transitions: HashMap<usize, TransitionConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auto_advance: Option<AutoAdvanceConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timer: Option<TimerConfig>,
+    /// Files to play instead of the media named by annotations of the PDF, by the named path,
+    /// e.g. `{"clip.mp4": "media/clip.mp4"}`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pdf_media: HashMap<PathBuf, PathBuf>,
 }
 
 impl Config {
+    /// JSON Schema of the configuration file, e.g. for editor autocompletion.
+    pub fn json_schema() -> RootSchema {
+        schemars::schema_for!(Config)
+    }
+
     pub fn slides_map(self) -> HashMap<usize, Vec<VideoEntry>> {
         self.entries
             .into_iter()
             .fold(HashMap::new(), |mut acc, entry| {
                 for slide_num in entry.slide_nums.as_vec() {
-                    acc.entry(slide_num).or_default().push(VideoEntry {
-                        slide_nums: entry.slide_nums.as_vec(),
-                        video_path: entry.video_path.clone(),
-                        pos: PosRequest {
-                            width: entry.pos.0,
-                            height: entry.pos.1,
-                        },
-                        size: entry.size.as_size_request(),
-                    });
+                    acc.entry(slide_num)
+                        .or_default()
+                        .push(entry.as_video_entry());
                 }
                 acc
             })
@@ -92,18 +562,182 @@ impl Config {
 
     pub fn video_entries(self) -> Vec<VideoEntry> {
         self.entries
-            .into_iter()
-            .map(|entry| VideoEntry {
-                slide_nums: entry.slide_nums.as_vec(),
-                video_path: entry.video_path,
-                pos: PosRequest {
-                    width: entry.pos.0,
-                    height: entry.pos.1,
-                },
-                size: entry.size.as_size_request(),
-            })
+            .iter()
+            .map(VideoConfig::as_video_entry)
             .collect()
     }
+
+    pub fn audio_entries(&self) -> Vec<AudioEntry> {
+        self.audio.iter().map(AudioConfig::as_audio_entry).collect()
+    }
+
+    pub fn auto_advance(&self) -> Option<AutoAdvance> {
+        self.auto_advance
+            .as_ref()
+            .map(AutoAdvanceConfig::as_auto_advance)
+    }
+
+    pub fn timer_settings(&self) -> TimerSettings {
+        self.timer
+            .as_ref()
+            .map(TimerConfig::as_timer_settings)
+            .unwrap_or_default()
+    }
+
+    pub fn transitions(&self) -> Transitions {
+        Transitions {
+            default: self
+                .transition
+                .as_ref()
+                .map(TransitionConfig::as_transition),
+            slides: self
+                .slide_transitions
+                .iter()
+                .map(|(page_idx, transition)| (*page_idx, transition.as_transition()))
+                .collect(),
+        }
+    }
+
+    /// Locations (e.g. `auto_advance.duration`) and values of the durations that are negative,
+    /// not a number or too long, sorted by location.
+    pub fn invalid_durations(&self) -> Vec<(String, f32)> {
+        let mut durations: Vec<(String, f32)> = self
+            .audio
+            .iter()
+            .enumerate()
+            .map(|(idx, audio)| (format!("audio[{}].fade_out", idx), audio.fade_out))
+            .chain(
+                self.transition
+                    .map(|transition| ("transition.duration".to_owned(), transition.duration)),
+            )
+            .chain(self.slide_transitions.iter().map(|(page_idx, transition)| {
+                (
+                    format!("slide_transitions.{}.duration", page_idx),
+                    transition.duration,
+                )
+            }))
+            .collect();
+        if let Some(auto_advance) = &self.auto_advance {
+            durations.extend(
+                auto_advance
+                    .duration
+                    .map(|duration| ("auto_advance.duration".to_owned(), duration)),
+            );
+            durations.extend(
+                auto_advance
+                    .slide_durations
+                    .iter()
+                    .map(|(page_idx, duration)| {
+                        (
+                            format!("auto_advance.slide_durations.{}", page_idx),
+                            *duration,
+                        )
+                    }),
+            );
+        }
+        if let Some(timer) = &self.timer {
+            durations.extend(
+                timer
+                    .duration
+                    .map(|duration| ("timer.duration".to_owned(), duration)),
+            );
+            durations.extend(
+                timer.slide_budgets.iter().map(|(page_idx, budget)| {
+                    (format!("timer.slide_budgets.{}", page_idx), *budget)
+                }),
+            );
+        }
+        durations.retain(|(_, duration)| seconds(*duration).is_none());
+        durations.sort_by(|a, b| a.0.cmp(&b.0));
+        durations
+    }
+
+    pub fn pdf_media(&self) -> HashMap<PathBuf, PathBuf> {
+        self.pdf_media.clone()
+    }
+
+    /// Plays `media_path` instead of the media named `named_path` by annotations of the PDF.
+    pub fn with_pdf_media(mut self, named_path: PathBuf, media_path: PathBuf) -> Self {
+        self.pdf_media.insert(named_path, media_path);
+        self
+    }
+
+    /// Paths of all media files, i.e. the `video_path`s, `audio_path`s and files of `pdf_media`.
+    ///
+    /// Custom overlays are left out, their `video_path` is not necessarily a file.
+    pub fn packable_media_paths(&self) -> Vec<PathBuf> {
+        self.entries
+            .iter()
+            .filter(|entry| !matches!(entry.media_type, MediaType::Custom(_)))
+            .map(|entry| entry.video_path.clone())
+            .chain(self.audio.iter().map(|audio| audio.audio_path.clone()))
+            .chain(self.pdf_media.values().cloned())
+            .collect()
+    }
+
+    /// Replaces the `video_path` of every entry (except of custom overlays), the `audio_path` of
+    /// every audio track and the files of `pdf_media`.
+    pub fn map_media_paths(mut self, mut f: impl FnMut(&Path) -> PathBuf) -> Self {
+        for entry in self.entries.iter_mut() {
+            if !matches!(entry.media_type, MediaType::Custom(_)) {
+                entry.video_path = f(&entry.video_path);
+            }
+        }
+        for audio in self.audio.iter_mut() {
+            audio.audio_path = f(&audio.audio_path);
+        }
+        for media_path in self.pdf_media.values_mut() {
+            *media_path = f(media_path);
+        }
+        self
+    }
+
+    /// Takes over position and size of the given video entries (matched by their index) into
+    /// the JSON of a configuration file and appends the ones without counterpart.
+    ///
+    /// Everything else is left untouched, including keys unknown to bewegtbild such as `$schema`.
+    pub fn patch_video_entries(
+        config: &mut serde_json::Value,
+        video_entries: &[VideoEntry],
+    ) -> Result<(), String> {
+        let entries = config
+            .as_object_mut()
+            .ok_or("the configuration is not a JSON object")?
+            .entry("entries")
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()))
+            .as_array_mut()
+            .ok_or("`entries` is not a list")?;
+        for (idx, video_entry) in video_entries.iter().enumerate() {
+            match entries.get_mut(idx) {
+                Some(entry) => {
+                    let entry = entry
+                        .as_object_mut()
+                        .ok_or_else(|| format!("`entries[{}]` is not a JSON object", idx))?;
+                    // keep the form of the size, e.g. only a width
+                    let size = match entry.get("size") {
+                        Some(size) => serde_json::from_value::<SizeRequestConfig>(size.clone())
+                            .map_err(|e| format!("entries[{}].size: {}", idx, e))?,
+                        None => SizeRequestConfig::Width(SizeEntry::Percent(30.0)),
+                    };
+                    let pos = PosRequestConfig(video_entry.pos.width, video_entry.pos.height);
+                    entry.insert(
+                        "pos".to_owned(),
+                        serde_json::to_value(pos).map_err(|e| e.to_string())?,
+                    );
+                    entry.insert(
+                        "size".to_owned(),
+                        serde_json::to_value(size.with_size_request(video_entry.size))
+                            .map_err(|e| e.to_string())?,
+                    );
+                }
+                None => entries.push(
+                    serde_json::to_value(VideoConfig::from_video_entry(video_entry))
+                        .map_err(|e| e.to_string())?,
+                ),
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Serialize for SizeEntry {
@@ -117,6 +751,114 @@ impl Serialize for SizeEntry {
     }
 }
 
+impl JsonSchema for SizeEntry {
+    fn schema_name() -> String {
+        "SizeEntry".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some(r"^\d+(\.\d+)?%$".to_owned()),
+                ..Default::default()
+            })),
+            metadata: Some(Box::new(Metadata {
+                description: Some("A percentage of the slide such as 20%".to_owned()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl Serialize for ColorConfig {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let hex = String::deserialize(deserializer)?;
+        Color32::from_hex(&hex).map(ColorConfig).map_err(|_| {
+            serde::de::Error::custom(format!(
+                "`{}` is not a colour such as #ff0000 or #00000080",
+                hex
+            ))
+        })
+    }
+}
+
+impl JsonSchema for ColorConfig {
+    fn schema_name() -> String {
+        "Color".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some(r"^#([0-9a-fA-F]{3,4}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})$".to_owned()),
+                ..Default::default()
+            })),
+            metadata: Some(Box::new(Metadata {
+                description: Some("Colour as hex string such as #ff0000 or #00000080".to_owned()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl Serialize for KeyConfig {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.0.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        egui::Key::from_name(&name).map(KeyConfig).ok_or_else(|| {
+            serde::de::Error::custom(format!("`{}` is not a key such as Z or F5", name))
+        })
+    }
+}
+
+impl JsonSchema for KeyConfig {
+    fn schema_name() -> String {
+        "Key".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(egui::Key::ALL.iter().map(|key| key.name().into()).collect()),
+            metadata: Some(Box::new(Metadata {
+                description: Some("Name of a key such as Z or F5".to_owned()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 impl<'de> Deserialize<'de> for SizeEntry {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -167,4 +909,196 @@ mod test {
             serde_json::from_str("[\"50.0%\", \"10.0%\"]").unwrap()
         );
     }
+
+    #[test]
+    fn parse_media_type() {
+        let config: Config = serde_json::from_str(
+            r#"{"entries": [
+                {"video_path": "a.mp4", "slide_num": 1, "size": "20%"},
+                {"video_path": "b.gif", "type": "animated_image", "slide_num": 1, "size": "20%"},
+                {"video_path": "c.csv", "type": "heatmap", "slide_num": 1, "size": "20%"}
+            ]}"#,
+        )
+        .unwrap();
+        let media_types: Vec<MediaType> = config
+            .video_entries()
+            .into_iter()
+            .map(|entry| entry.media_type)
+            .collect();
+        assert_eq!(
+            media_types,
+            vec![
+                MediaType::Video,
+                MediaType::AnimatedImage,
+                MediaType::Custom("heatmap".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_audio_config() {
+        let config: Config = serde_json::from_str(
+            r#"{"entries": [], "audio": [{"audio_path": "a.mp3", "slide_range": [4, 12]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.audio_entries(),
+            vec![AudioEntry {
+                audio_path: PathBuf::from("a.mp3"),
+                slide_range: 4..=12,
+                volume: 1.0,
+                fade_out: Duration::from_secs(1),
+                looping: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_color_config() {
+        assert_eq!(
+            ColorConfig(Color32::from_black_alpha(128)),
+            serde_json::from_str("\"#00000080\"").unwrap()
+        );
+        assert!(serde_json::from_str::<ColorConfig>("\"black\"").is_err());
+    }
+
+    #[test]
+    fn parse_transitions() {
+        let config: Config = serde_json::from_str(
+            r#"{"entries": [], "transition": {"type": "fade"},
+                "slide_transitions": {"2": {"type": "none"}}}"#,
+        )
+        .unwrap();
+        let transitions = config.transitions();
+        assert_eq!(
+            transitions.to_slide(1),
+            Some(Transition {
+                kind: TransitionKind::Fade,
+                duration: Duration::from_millis(500)
+            })
+        );
+        assert_eq!(transitions.to_slide(2), None);
+
+        // switches instantly instead of panicking
+        let config: Config = serde_json::from_str(
+            r#"{"entries": [], "transition": {"type": "fade", "duration": 1e20}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.transitions().to_slide(1), None);
+        assert_eq!(
+            config.invalid_durations(),
+            vec![("transition.duration".to_owned(), 1e20)]
+        );
+    }
+
+    #[test]
+    fn parse_chroma_key_config() {
+        let config: Config = serde_json::from_str(
+            r##"{"entries": [
+                {"video_path": "a.mp4", "slide_num": 1, "size": "20%", "chroma_key": {"color": "#00ff00"}}
+            ]}"##,
+        )
+        .unwrap();
+        assert_eq!(
+            config.video_entries()[0].appearance.chroma_key,
+            Some(ChromaKey {
+                color: Color32::GREEN,
+                tolerance: 0.3,
+                softness: 0.1
+            })
+        );
+    }
+
+    #[test]
+    fn skip_invalid_durations() {
+        let config: Config = serde_json::from_str(
+            r#"{"entries": [], "auto_advance": {"duration": 1e20,
+                "slide_durations": {"2": -1.5, "3": 4}}}"#,
+        )
+        .unwrap();
+        let auto_advance = config.auto_advance().unwrap();
+        assert_eq!(auto_advance.duration, None);
+        assert_eq!(
+            auto_advance.slide_durations,
+            HashMap::from([(3, Duration::from_secs(4))])
+        );
+        assert_eq!(
+            config.invalid_durations(),
+            vec![
+                ("auto_advance.duration".to_owned(), 1e20),
+                ("auto_advance.slide_durations.2".to_owned(), -1.5),
+            ]
+        );
+
+        let config: Config = serde_json::from_str(
+            r#"{"entries": [], "timer": {"duration": 600, "slide_budgets": {"1": 1e30}}}"#,
+        )
+        .unwrap();
+        let timer_settings = config.timer_settings();
+        assert_eq!(timer_settings.duration, Some(Duration::from_secs(600)));
+        assert!(timer_settings.slide_budgets.is_empty());
+        assert_eq!(
+            config.invalid_durations(),
+            vec![("timer.slide_budgets.1".to_owned(), 1e30)]
+        );
+
+        let config: Config = serde_json::from_str(
+            r#"{"entries": [], "audio": [{"audio_path": "a.mp3", "slide_range": [0, 1],
+                "fade_out": -2}]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.audio_entries()[0].fade_out, Duration::ZERO);
+        assert_eq!(
+            config.invalid_durations(),
+            vec![("audio[0].fade_out".to_owned(), -2.0)]
+        );
+    }
+
+    #[test]
+    fn map_pdf_media() {
+        let config: Config =
+            serde_json::from_str(r#"{"entries": [], "pdf_media": {"clip.mp4": "new.mp4"}}"#)
+                .unwrap();
+        assert_eq!(
+            config.packable_media_paths(),
+            vec![PathBuf::from("new.mp4")]
+        );
+        let config = config.map_media_paths(|media_path| Path::new("media").join(media_path));
+        assert_eq!(
+            config.pdf_media().get(Path::new("clip.mp4")),
+            Some(&PathBuf::from("media/new.mp4"))
+        );
+    }
+
+    #[test]
+    fn update_video_entries() {
+        let config_str = r#"{"$schema": "bewegtbild.schema.json", "entries": [
+                {"video_path": "a.mp4", "slide_num": [1, 2], "size": ["20%"], "note": "intro"},
+                {"video_path": "b.mp4", "slide_num": 3, "size": "20%"}
+            ], "speaker": "me"}"#;
+        let config: Config = serde_json::from_str(config_str).unwrap();
+        let mut video_entries = config.video_entries();
+        video_entries[0].pos.width = SizeEntry::Percent(10.0);
+        video_entries[0].size = SizeRequest::AutoHeight(SizeEntry::Percent(40.0));
+        let mut new_entry = video_entries[1].clone();
+        new_entry.slide_nums = vec![4];
+        video_entries.push(new_entry);
+
+        let mut value: serde_json::Value = serde_json::from_str(config_str).unwrap();
+        Config::patch_video_entries(&mut value, &video_entries).unwrap();
+        // keys unknown to bewegtbild survive
+        assert_eq!(value["$schema"], "bewegtbild.schema.json");
+        assert_eq!(value["speaker"], "me");
+        assert_eq!(value["entries"][0]["note"], "intro");
+
+        let config: Config = serde_json::from_value(value).unwrap();
+        assert_eq!(config.entries.len(), 3);
+        assert_eq!(config.entries[0].pos.0, SizeEntry::Percent(10.0));
+        assert_eq!(
+            config.entries[0].size,
+            SizeRequestConfig::WidthTuple((SizeEntry::Percent(40.0),))
+        );
+        assert_eq!(config.entries[0].slide_nums.as_vec(), vec![1, 2]);
+        assert_eq!(config.entries[2].slide_nums.as_vec(), vec![4]);
+    }
 }
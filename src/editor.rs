@@ -0,0 +1,318 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use egui::{Color32, CursorIcon, Pos2, Rect, Sense, Stroke, StrokeKind, Vec2};
+
+use crate::{
+    slides::SlidesCache, Appearance, Config, MediaType, PosRequest, SizeEntry, SizeRequest,
+    VideoEntry,
+};
+
+/// Distance (in points) in which dragged edges snap to the edges / centre of the slide
+const SNAP_DISTANCE: f32 = 8.0;
+/// Side length of the resize handles
+const HANDLE_SIZE: f32 = 10.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Hash)]
+enum Handle {
+    Move,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Handle {
+    const CORNERS: [Handle; 4] = [
+        Handle::TopLeft,
+        Handle::TopRight,
+        Handle::BottomLeft,
+        Handle::BottomRight,
+    ];
+
+    fn pos(&self, rect: Rect) -> Pos2 {
+        match self {
+            Handle::Move => rect.center(),
+            Handle::TopLeft => rect.left_top(),
+            Handle::TopRight => rect.right_top(),
+            Handle::BottomLeft => rect.left_bottom(),
+            Handle::BottomRight => rect.right_bottom(),
+        }
+    }
+
+    fn opposite(&self) -> Handle {
+        match self {
+            Handle::Move => Handle::Move,
+            Handle::TopLeft => Handle::BottomRight,
+            Handle::TopRight => Handle::BottomLeft,
+            Handle::BottomLeft => Handle::TopRight,
+            Handle::BottomRight => Handle::TopLeft,
+        }
+    }
+
+    fn cursor(&self) -> CursorIcon {
+        match self {
+            Handle::Move => CursorIcon::Grab,
+            Handle::TopLeft | Handle::BottomRight => CursorIcon::ResizeNwSe,
+            Handle::TopRight | Handle::BottomLeft => CursorIcon::ResizeNeSw,
+        }
+    }
+}
+
+struct Drag {
+    /// Index of the dragged video entry
+    idx: usize,
+    handle: Handle,
+    start_rect: Rect,
+    delta: Vec2,
+}
+
+impl Drag {
+    /// Rect of the video given the accumulated movement of the mouse.
+    fn rect(&self, keep_aspect: bool, snap_to: Option<Rect>) -> Rect {
+        let start = self.start_rect;
+        if self.handle == Handle::Move {
+            let rect = start.translate(self.delta);
+            return match snap_to {
+                Some(slide) => rect.translate(Vec2::new(
+                    snap_offset(
+                        [rect.left(), rect.center().x, rect.right()],
+                        [slide.left(), slide.center().x, slide.right()],
+                    ),
+                    snap_offset(
+                        [rect.top(), rect.center().y, rect.bottom()],
+                        [slide.top(), slide.center().y, slide.bottom()],
+                    ),
+                )),
+                None => rect,
+            };
+        }
+
+        let anchor = self.handle.opposite().pos(start);
+        let mut moving = self.handle.pos(start) + self.delta;
+        if let Some(slide) = snap_to {
+            moving.x += snap_offset([moving.x], [slide.left(), slide.center().x, slide.right()]);
+            moving.y += snap_offset([moving.y], [slide.top(), slide.center().y, slide.bottom()]);
+        }
+        let rect = Rect::from_two_pos(anchor, moving);
+        if !keep_aspect || start.width() <= 0.0 {
+            return rect;
+        }
+        // the height follows the width, growing away from the anchor
+        let height = rect.width() * start.height() / start.width();
+        if anchor.y <= moving.y {
+            Rect::from_min_size(
+                egui::pos2(rect.left(), anchor.y),
+                egui::vec2(rect.width(), height),
+            )
+        } else {
+            Rect::from_min_max(
+                egui::pos2(rect.left(), anchor.y - height),
+                egui::pos2(rect.right(), anchor.y),
+            )
+        }
+    }
+}
+
+/// Offset to move the closest of `values` onto one of the `targets`, if close enough.
+fn snap_offset<const N: usize, const M: usize>(values: [f32; N], targets: [f32; M]) -> f32 {
+    values
+        .iter()
+        .flat_map(|value| targets.iter().map(move |target| target - value))
+        .filter(|offset| offset.abs() < SNAP_DISTANCE)
+        .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+        .unwrap_or(0.0)
+}
+
+/// Percentage of `value` w.r.t. `total`, rounded to one decimal to keep the config readable.
+fn percent(value: f32, total: f32) -> SizeEntry {
+    SizeEntry::Percent(((value / total * 1000.0).round() / 10.0).clamp(0.0, 100.0))
+}
+
+/// Moves the video entry to the given rect on the slide.
+fn place(entry: &mut VideoEntry, rect: Rect, slide_rect: Rect) {
+    let offset = rect.min - slide_rect.min;
+    entry.pos = PosRequest {
+        width: percent(offset.x, slide_rect.width()),
+        height: percent(offset.y, slide_rect.height()),
+    };
+    let width = percent(rect.width(), slide_rect.width());
+    let height = percent(rect.height(), slide_rect.height());
+    entry.size = match entry.size {
+        SizeRequest::Size(..) => SizeRequest::Size(width, height),
+        SizeRequest::AutoWidth(_) => SizeRequest::AutoWidth(height),
+        SizeRequest::AutoHeight(_) => SizeRequest::AutoHeight(width),
+    };
+}
+
+/// Writes the video entries back to the configuration, keeping the rest of the file as is.
+fn write_config(config_path: &Path, video_entries: &[VideoEntry]) -> Result<(), String> {
+    let mut config = if config_path.exists() {
+        let config_str = fs::read_to_string(config_path).map_err(|e| e.to_string())?;
+        serde_json::from_str::<serde_json::Value>(&config_str).map_err(|e| e.to_string())?
+    } else {
+        serde_json::to_value(Config::default()).map_err(|e| e.to_string())?
+    };
+    Config::patch_video_entries(&mut config, video_entries)?;
+    let config_str = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+    fs::write(config_path, config_str).map_err(|e| e.to_string())
+}
+
+/// Edit mode to place the videos of the current slide with the mouse.
+pub struct Editor {
+    pub active: bool,
+    /// Configuration file to write the changes to
+    config_path: Option<PathBuf>,
+    snap: bool,
+    drag: Option<Drag>,
+    status: String,
+}
+
+impl Editor {
+    pub fn new(config_path: Option<PathBuf>) -> Self {
+        Self {
+            active: false,
+            config_path,
+            snap: true,
+            drag: None,
+            status: String::new(),
+        }
+    }
+
+    /// Draws the handles of the videos rendered on this slide and applies changes to them.
+    pub fn ui(
+        &mut self,
+        ctx: &egui::Context,
+        ui: &mut egui::Ui,
+        slides: &mut SlidesCache,
+        page_idx: usize,
+        slide_rect: Rect,
+    ) {
+        self.toolbar(ctx, slides, page_idx);
+
+        let color = Color32::from_rgb(255, 140, 0);
+        for (idx, rect) in slides.video_rects() {
+            ui.painter()
+                .rect_stroke(rect, 0.0, Stroke::new(2.0, color), StrokeKind::Outside);
+            let response = ui
+                .interact(rect, egui::Id::new(("editor", idx)), Sense::drag())
+                .on_hover_cursor(Handle::Move.cursor());
+            self.handle_drag(&response, idx, Handle::Move, rect);
+            for handle in Handle::CORNERS {
+                let handle_rect =
+                    Rect::from_center_size(handle.pos(rect), Vec2::splat(HANDLE_SIZE));
+                ui.painter().rect_filled(handle_rect, 0.0, color);
+                let response = ui
+                    .interact(
+                        handle_rect,
+                        egui::Id::new(("editor", idx, handle)),
+                        Sense::drag(),
+                    )
+                    .on_hover_cursor(handle.cursor());
+                self.handle_drag(&response, idx, handle, rect);
+            }
+        }
+
+        if let Some(drag) = &self.drag {
+            if self.snap {
+                let stroke = Stroke::new(1.0, color.gamma_multiply(0.5));
+                ui.painter()
+                    .vline(slide_rect.center().x, slide_rect.y_range(), stroke);
+                ui.painter()
+                    .hline(slide_rect.x_range(), slide_rect.center().y, stroke);
+            }
+            if let Some(entry) = slides.video_entry_mut(drag.idx) {
+                let keep_aspect = !matches!(entry.size, SizeRequest::Size(..));
+                let rect = drag.rect(keep_aspect, self.snap.then_some(slide_rect));
+                place(entry, rect, slide_rect);
+            }
+        }
+    }
+
+    fn handle_drag(&mut self, response: &egui::Response, idx: usize, handle: Handle, rect: Rect) {
+        if response.drag_started() {
+            self.drag = Some(Drag {
+                idx,
+                handle,
+                start_rect: rect,
+                delta: Vec2::ZERO,
+            });
+        }
+        if response.dragged() {
+            if let Some(drag) = self
+                .drag
+                .as_mut()
+                .filter(|drag| drag.idx == idx && drag.handle == handle)
+            {
+                drag.delta += response.drag_delta();
+            }
+        }
+        if response.drag_stopped() {
+            self.drag = None;
+            self.status = "Unsaved changes".to_owned();
+        }
+    }
+
+    fn toolbar(&mut self, ctx: &egui::Context, slides: &mut SlidesCache, page_idx: usize) {
+        egui::Window::new("Edit videos")
+            .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut self.snap, "Snap to edges and centre");
+                ui.horizontal(|ui| {
+                    if ui.button("Add media…").clicked() {
+                        self.add_video(slides, page_idx);
+                    }
+                    if ui.button("Save").clicked() {
+                        self.save(slides);
+                    }
+                });
+                if !self.status.is_empty() {
+                    ui.label(self.status.as_str());
+                }
+                ui.weak("Press E to leave the edit mode");
+            });
+    }
+
+    fn add_video(&mut self, slides: &mut SlidesCache, page_idx: usize) {
+        let Some(video_path) = rfd::FileDialog::new()
+            .set_title("Add video or image")
+            .pick_file()
+        else {
+            return;
+        };
+        slides.push_video_entry(VideoEntry {
+            slide_nums: vec![page_idx],
+            media_type: MediaType::from_path(&video_path),
+            fps: None,
+            video_path,
+            pos: PosRequest {
+                width: SizeEntry::Percent(10.0),
+                height: SizeEntry::Percent(10.0),
+            },
+            size: SizeRequest::default(),
+            appearance: Appearance::default(),
+            animation: None,
+        });
+        self.status = "Unsaved changes".to_owned();
+    }
+
+    fn save(&mut self, slides: &SlidesCache) {
+        if self.config_path.is_none() {
+            self.config_path = rfd::FileDialog::new()
+                .set_title("Save configuration")
+                .add_filter("JSON", &["json"])
+                .save_file();
+        }
+        let Some(config_path) = &self.config_path else {
+            return;
+        };
+        self.status = match write_config(config_path, &slides.video_entries()) {
+            Ok(()) => format!("Saved to {}", config_path.to_string_lossy()),
+            Err(e) => format!("Could not save: {}", e),
+        };
+    }
+}
@@ -0,0 +1,50 @@
+/// Slides jumped away from, to return there, like the history of a browser.
+///
+/// Only jumps count (e.g. following a link or `G`), stepping through the slides does not.
+#[derive(Default)]
+pub struct JumpHistory {
+    back: Vec<usize>,
+    forward: Vec<usize>,
+}
+
+impl JumpHistory {
+    /// Records a jump from the given slide, discarding the slides to go forward to.
+    pub fn jump(&mut self, from_page_idx: usize) {
+        self.back.push(from_page_idx);
+        self.forward.clear();
+    }
+
+    /// Slide to return to from the current one, if any.
+    pub fn back(&mut self, page_idx: usize) -> Option<usize> {
+        let back_page_idx = self.back.pop()?;
+        self.forward.push(page_idx);
+        Some(back_page_idx)
+    }
+
+    /// Slide to go forward to again after going back, if any.
+    pub fn forward(&mut self, page_idx: usize) -> Option<usize> {
+        let forward_page_idx = self.forward.pop()?;
+        self.back.push(page_idx);
+        Some(forward_page_idx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn go_back_and_forward() {
+        let mut history = JumpHistory::default();
+        assert_eq!(history.back(3), None);
+        history.jump(3);
+        history.jump(20);
+        assert_eq!(history.back(5), Some(20));
+        assert_eq!(history.back(20), Some(3));
+        assert_eq!(history.forward(3), Some(20));
+        // a new jump discards the way forward
+        history.jump(20);
+        assert_eq!(history.forward(7), None);
+        assert_eq!(history.back(7), Some(20));
+    }
+}
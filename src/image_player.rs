@@ -0,0 +1,239 @@
+use std::{
+    cell::OnceCell,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use egui::{ColorImage, TextureHandle};
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    AnimationDecoder, Frame, ImageFormat, ImageReader, ImageResult,
+};
+
+use crate::SlideOverlay;
+
+/// Frames are shown at least this long, some GIFs specify a delay of 0
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+
+/// Decodes all frames of an animated GIF, APNG or WebP.
+///
+/// Other formats (and images without animation) result in a single frame.
+fn decode_frames(path: &Path, animated: bool) -> ImageResult<Vec<Frame>> {
+    let open = || File::open(path).map(BufReader::new);
+    let format = ImageReader::new(open()?).with_guessed_format()?.format();
+    if animated {
+        match format {
+            Some(ImageFormat::Gif) => {
+                return GifDecoder::new(open()?)?.into_frames().collect_frames()
+            }
+            Some(ImageFormat::Png) => {
+                let decoder = PngDecoder::new(open()?)?;
+                if decoder.is_apng()? {
+                    return decoder.apng()?.into_frames().collect_frames();
+                }
+            }
+            Some(ImageFormat::WebP) => {
+                let decoder = WebPDecoder::new(open()?)?;
+                if decoder.has_animation() {
+                    return decoder.into_frames().collect_frames();
+                }
+            }
+            _ => {}
+        }
+    }
+    let image = ImageReader::new(open()?)
+        .with_guessed_format()?
+        .decode()?
+        .to_rgba8();
+    Ok(vec![Frame::new(image)])
+}
+
+struct ImageFrame {
+    texture: TextureHandle,
+    delay: Duration,
+}
+
+pub struct PlayingImage {
+    frames: Vec<ImageFrame>,
+    /// Duration of one loop through all frames
+    duration: Duration,
+    size: egui::Vec2,
+}
+
+impl PlayingImage {
+    /// Decodes the frames and uploads them as textures.
+    fn load(ctx: &egui::Context, path: &Path, animated: bool) -> Result<Self, String> {
+        let frames: Vec<ImageFrame> = decode_frames(path, animated)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .enumerate()
+            .map(|(idx, frame)| {
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                let delay = Duration::from_secs_f64(numer as f64 / denom.max(1) as f64 / 1000.0)
+                    .max(MIN_FRAME_DELAY);
+                let buffer = frame.into_buffer();
+                let size = [buffer.width() as usize, buffer.height() as usize];
+                let color_image =
+                    ColorImage::from_rgba_unmultiplied(size, buffer.as_flat_samples().as_slice());
+                ImageFrame {
+                    texture: ctx.load_texture(
+                        format!("{}#{}", path.to_string_lossy(), idx),
+                        color_image,
+                        Default::default(),
+                    ),
+                    delay,
+                }
+            })
+            .collect();
+        let size = frames
+            .first()
+            .ok_or("the image has no frames")?
+            .texture
+            .size_vec2();
+        let duration = if frames.len() > 1 {
+            frames.iter().map(|frame| frame.delay).sum()
+        } else {
+            Duration::ZERO
+        };
+        Ok(Self {
+            frames,
+            duration,
+            size,
+        })
+    }
+
+    fn current_frame(&self, elapsed: Duration) -> Option<&ImageFrame> {
+        if self.duration.is_zero() {
+            return self.frames.first();
+        }
+        let mut time_in_loop =
+            Duration::from_nanos((elapsed.as_nanos() % self.duration.as_nanos()) as u64);
+        for frame in &self.frames {
+            if time_in_loop < frame.delay {
+                return Some(frame);
+            }
+            time_in_loop -= frame.delay;
+        }
+        self.frames.last()
+    }
+}
+
+/// Lightweight player for (animated) images, decoded by the `image` crate instead of ffmpeg.
+///
+/// The frames are decoded by a background thread, so that long animations do not block the UI;
+/// the player has no size until they are done.
+pub struct ImagePlayer {
+    /// Whether to decode all frames of an animation or the first one only
+    animated: bool,
+    /// Image being decoded, from `init` until destroyed
+    decoding: Option<Receiver<Result<PlayingImage, String>>>,
+    /// Taken over from `decoding` once done, `None` if decoding failed
+    image: OnceCell<Option<PlayingImage>>,
+    started: Option<Instant>,
+    /// Time into the animation at which it has been paused
+    paused_at: Option<Duration>,
+}
+
+impl ImagePlayer {
+    pub fn new(animated: bool) -> Self {
+        Self {
+            animated,
+            decoding: None,
+            image: OnceCell::new(),
+            started: None,
+            paused_at: None,
+        }
+    }
+
+    /// Reads the dimensions of the image to check whether it can be decoded.
+    pub fn probe(image_path: &str) -> Result<egui::Vec2, String> {
+        let (width, height) = image::image_dimensions(image_path).map_err(|e| e.to_string())?;
+        Ok(egui::vec2(width as f32, height as f32))
+    }
+
+    /// The decoded image, `None` while still decoding or if decoding failed.
+    fn image(&self) -> Option<&PlayingImage> {
+        if self.image.get().is_none() {
+            let result = match self.decoding.as_ref()?.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => Err("Image decoder stopped".to_owned()),
+            };
+            let image = result.map_err(|e| println!("{}", e)).ok();
+            let _ = self.image.set(image);
+        }
+        self.image.get()?.as_ref()
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.paused_at.unwrap_or_else(|| {
+            self.started
+                .map(|started| started.elapsed())
+                .unwrap_or_default()
+        })
+    }
+}
+
+impl SlideOverlay for ImagePlayer {
+    fn init(&mut self, ctx: &egui::Context, image_path: &str) {
+        let (image_tx, image_rx) = mpsc::channel();
+        let path = PathBuf::from(image_path);
+        let animated = self.animated;
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let image = PlayingImage::load(&ctx, &path, animated)
+                .map_err(|e| format!("Could not decode image `{}`: {}", path.to_string_lossy(), e));
+            // the player may have been destroyed in the meantime
+            let _ = image_tx.send(image);
+            ctx.request_repaint();
+        });
+        self.decoding = Some(image_rx);
+        self.image = OnceCell::new();
+    }
+
+    fn start(&mut self) {
+        let paused_at = self.paused_at.take().unwrap_or_default();
+        self.started = Some(Instant::now() - paused_at);
+    }
+
+    fn render(&mut self, ui: &mut egui::Ui, rect: egui::Rect) -> Option<egui::Response> {
+        let image = self.image()?;
+        let frame = image.current_frame(self.elapsed())?;
+        if !image.duration.is_zero() && self.paused_at.is_none() {
+            ui.ctx().request_repaint_after(MIN_FRAME_DELAY);
+        }
+        Some(ui.put(
+            rect,
+            egui::Image::new((frame.texture.id(), rect.size())).fit_to_exact_size(rect.size()),
+        ))
+    }
+
+    fn pause(&mut self) {
+        let elapsed = self.started.map(|started| started.elapsed());
+        self.paused_at = self.paused_at.or(elapsed);
+    }
+
+    fn is_playing(&self) -> bool {
+        self.decoding.is_some()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.image()
+            .is_some_and(|image| self.elapsed() >= image.duration)
+    }
+
+    fn destroy(&mut self) {
+        self.decoding = None;
+        self.image = OnceCell::new();
+        self.started = None;
+        self.paused_at = None;
+    }
+
+    fn size(&self) -> Option<egui::Vec2> {
+        self.image().map(|image| image.size)
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use egui::{ColorImage, TextureHandle};
+
+use crate::SlideOverlay;
+
+/// Frame rate of image sequences without a configured `fps`
+pub const DEFAULT_FPS: f32 = 25.0;
+/// Number of frames decoded ahead of time
+const DECODE_AHEAD: usize = 8;
+
+const FRAME_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "bmp", "tif", "tiff", "webp"];
+
+/// Whether the path refers to an image sequence, i.e. a directory of frames or a glob such as
+/// `frames/frame_*.png`.
+pub fn is_image_sequence(path: &Path) -> bool {
+    path.is_dir()
+        || path
+            .file_name()
+            .is_some_and(|name| is_glob(&name.to_string_lossy()))
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Matches a file name against a glob pattern supporting `*` and `?`.
+fn matches_glob(pattern: &str, name: &str) -> bool {
+    match pattern.chars().next() {
+        None => name.is_empty(),
+        Some('*') => {
+            let rest = &pattern[1..];
+            name.char_indices()
+                .map(|(idx, _)| idx)
+                .chain([name.len()])
+                .any(|idx| matches_glob(rest, &name[idx..]))
+        }
+        Some(c) => {
+            let mut chars = name.chars();
+            match chars.next() {
+                Some(n) if c == '?' || c == n => {
+                    matches_glob(&pattern[c.len_utf8()..], chars.as_str())
+                }
+                _ => false,
+            }
+        }
+    }
+}
+
+/// Sort key comparing digit runs by their value, so that `frame_10` follows `frame_9`.
+fn natural_key(name: &str) -> Vec<(String, u64)> {
+    let mut key: Vec<(String, u64)> = vec![(String::new(), 0)];
+    let mut in_number = false;
+    for c in name.chars() {
+        match c.to_digit(10) {
+            Some(digit) => {
+                let last = key.last_mut().unwrap();
+                last.1 = last.1.saturating_mul(10).saturating_add(digit as u64);
+                in_number = true;
+            }
+            None => {
+                if in_number {
+                    key.push((String::new(), 0));
+                    in_number = false;
+                }
+                key.last_mut().unwrap().0.push(c);
+            }
+        }
+    }
+    key
+}
+
+/// Paths of the frames of the sequence, in playback order.
+pub fn frame_paths(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let (dir, pattern) = if path.is_dir() {
+        (path, None)
+    } else {
+        let pattern = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        (dir, pattern)
+    };
+    let mut frames: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("{}: {}", dir.to_string_lossy(), e))?
+        .filter_map(|dir_entry| dir_entry.ok().map(|dir_entry| dir_entry.path()))
+        .filter(|frame| {
+            let name = frame
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .unwrap_or_default();
+            match &pattern {
+                Some(pattern) => matches_glob(pattern, &name),
+                None => frame
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| {
+                        FRAME_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+                    }),
+            }
+        })
+        .filter(|frame| frame.is_file())
+        .collect();
+    if frames.is_empty() {
+        return Err(format!("no frames found in `{}`", path.to_string_lossy()));
+    }
+    frames.sort_by_cached_key(|frame| {
+        natural_key(&frame.file_name().unwrap_or_default().to_string_lossy())
+    });
+    Ok(frames)
+}
+
+fn decode_frame(path: &Path) -> Option<ColorImage> {
+    match image::open(path) {
+        Ok(image) => {
+            let image = image.to_rgba8();
+            let size = [image.width() as usize, image.height() as usize];
+            Some(ColorImage::from_rgba_unmultiplied(
+                size,
+                image.as_flat_samples().as_slice(),
+            ))
+        }
+        Err(e) => {
+            println!("Could not decode frame `{}`: {}", path.to_string_lossy(), e);
+            None
+        }
+    }
+}
+
+/// Sends the decoded frames over and over until the receiver is gone, or stops if none of them
+/// can be decoded.
+fn decode_frames(frames: &[PathBuf], frames_tx: SyncSender<ColorImage>) {
+    loop {
+        let mut decoded_any = false;
+        for frame in frames {
+            let Some(image) = decode_frame(frame) else {
+                continue;
+            };
+            decoded_any = true;
+            if frames_tx.send(image).is_err() {
+                // the player has been destroyed
+                return;
+            }
+        }
+        if !decoded_any {
+            return;
+        }
+    }
+}
+
+pub struct PlayingSequence {
+    /// Decoded frames, in order and looping; the decoder stops once this is dropped
+    frames_rx: Receiver<ColorImage>,
+    texture: Option<TextureHandle>,
+    size: egui::Vec2,
+    /// Number of frames shown since the start
+    frames_shown: u64,
+    /// Number of frames in one loop
+    num_frames: u64,
+    started: Option<Instant>,
+    paused_at: Option<Duration>,
+}
+
+/// Plays a directory of frames (e.g. the output of a simulation) like a video.
+///
+/// The frames are streamed from disk and decoded ahead of time by a background thread.
+pub struct ImageSequencePlayer {
+    fps: f32,
+    sequence: Option<PlayingSequence>,
+    /// Opening failed, do not try again until destroyed
+    failed: bool,
+}
+
+impl ImageSequencePlayer {
+    pub fn new(fps: Option<f32>) -> Self {
+        Self {
+            fps: fps.filter(|fps| *fps > 0.0).unwrap_or(DEFAULT_FPS),
+            sequence: None,
+            failed: false,
+        }
+    }
+
+    /// Lists the frames and reads the dimensions of the first one.
+    pub fn probe(path: &Path) -> Result<egui::Vec2, String> {
+        let frames = frame_paths(path)?;
+        let (width, height) = image::image_dimensions(&frames[0])
+            .map_err(|e| format!("{}: {}", frames[0].to_string_lossy(), e))?;
+        Ok(egui::vec2(width as f32, height as f32))
+    }
+}
+
+impl SlideOverlay for ImageSequencePlayer {
+    fn init(&mut self, _ctx: &egui::Context, media_path: &str) {
+        if self.failed {
+            return;
+        }
+        let size = match Self::probe(Path::new(media_path)) {
+            Ok(size) => size,
+            Err(e) => {
+                println!("Could not open image sequence: {}", e);
+                self.failed = true;
+                return;
+            }
+        };
+        let frames = frame_paths(Path::new(media_path)).unwrap_or_default();
+        let num_frames = frames.len() as u64;
+        let (frames_tx, frames_rx) = mpsc::sync_channel(DECODE_AHEAD);
+        thread::spawn(move || decode_frames(&frames, frames_tx));
+        self.sequence = Some(PlayingSequence {
+            frames_rx,
+            texture: None,
+            size,
+            frames_shown: 0,
+            num_frames,
+            started: None,
+            paused_at: None,
+        });
+    }
+
+    fn start(&mut self) {
+        if let Some(sequence) = self.sequence.as_mut() {
+            let paused_at = sequence.paused_at.take().unwrap_or_default();
+            sequence.started = Some(Instant::now() - paused_at);
+        }
+    }
+
+    fn render(&mut self, ui: &mut egui::Ui, rect: egui::Rect) -> Option<egui::Response> {
+        let sequence = self.sequence.as_mut()?;
+        let elapsed = sequence.paused_at.unwrap_or_else(|| {
+            sequence
+                .started
+                .map(|started| started.elapsed())
+                .unwrap_or_default()
+        });
+        let due_frames = (elapsed.as_secs_f32() * self.fps) as u64 + 1;
+        // drops frames if decoding falls behind
+        let mut next_image = None;
+        while sequence.frames_shown < due_frames {
+            match sequence.frames_rx.try_recv() {
+                Ok(image) => {
+                    next_image = Some(image);
+                    sequence.frames_shown += 1;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    // every frame failed to decode, opening it again would not help
+                    self.sequence = None;
+                    self.failed = true;
+                    return None;
+                }
+            }
+        }
+        if let Some(image) = next_image {
+            match sequence.texture.as_mut() {
+                Some(texture) => texture.set(image, Default::default()),
+                None => {
+                    sequence.texture = Some(ui.ctx().load_texture(
+                        "image-sequence",
+                        image,
+                        Default::default(),
+                    ))
+                }
+            }
+        }
+        if sequence.paused_at.is_none() {
+            ui.ctx()
+                .request_repaint_after(Duration::from_secs_f32(1.0 / self.fps));
+        }
+        let texture = sequence.texture.as_ref()?;
+        Some(ui.put(
+            rect,
+            egui::Image::new((texture.id(), rect.size())).fit_to_exact_size(rect.size()),
+        ))
+    }
+
+    fn pause(&mut self) {
+        if let Some(sequence) = self.sequence.as_mut() {
+            let elapsed = sequence.started.map(|started| started.elapsed());
+            sequence.paused_at = sequence.paused_at.or(elapsed);
+        }
+    }
+
+    fn is_playing(&self) -> bool {
+        self.sequence.is_some()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.sequence
+            .as_ref()
+            .is_some_and(|sequence| sequence.frames_shown >= sequence.num_frames)
+    }
+
+    fn destroy(&mut self) {
+        self.sequence = None;
+        self.failed = false;
+    }
+
+    fn size(&self) -> Option<egui::Vec2> {
+        self.sequence.as_ref().map(|sequence| sequence.size)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn match_glob() {
+        assert!(matches_glob("frame_*.png", "frame_0001.png"));
+        assert!(matches_glob("frame_????.png", "frame_0001.png"));
+        assert!(matches_glob("*", "frame_0001.png"));
+        assert!(!matches_glob("frame_*.png", "frame_0001.jpg"));
+        assert!(!matches_glob("frame_???.png", "frame_0001.png"));
+    }
+
+    #[test]
+    fn unreadable_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        // the header of the PNG is intact, so that probing works, but its pixels are cut off
+        let mut png = Vec::new();
+        image::RgbaImage::new(4, 4)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let frame = dir.path().join("frame_1.png");
+        fs::write(&frame, &png[..png.len() - 20]).unwrap();
+        assert!(ImageSequencePlayer::probe(dir.path()).is_ok());
+
+        // the decoder gives up instead of trying again and again, the player notices it has
+        // stopped
+        let (frames_tx, frames_rx) = mpsc::sync_channel(DECODE_AHEAD);
+        decode_frames(&[frame], frames_tx);
+        assert_eq!(frames_rx.try_recv(), Err(TryRecvError::Disconnected));
+
+        // neither can a missing directory be opened
+        let ctx = egui::Context::default();
+        let mut player = ImageSequencePlayer::new(None);
+        player.init(&ctx, &dir.path().join("missing").to_string_lossy());
+        assert!(!player.is_playing());
+        assert!(player.failed);
+    }
+
+    #[test]
+    fn sort_frames_naturally() {
+        let mut names = vec![
+            "frame_10.png",
+            "frame_9.png",
+            "frame_100.png",
+            "frame_1.png",
+        ];
+        names.sort_by_key(|name| natural_key(name));
+        assert_eq!(
+            names,
+            vec![
+                "frame_1.png",
+                "frame_9.png",
+                "frame_10.png",
+                "frame_100.png"
+            ]
+        );
+    }
+}
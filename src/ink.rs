@@ -0,0 +1,319 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use egui::{Color32, Pos2, Rect, Stroke};
+use pdfium_render::prelude::PdfRenderConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{pdf::PdfRenderer, SizeEntry};
+
+/// Number of changes that can be undone
+const UNDO_DEPTH: usize = 100;
+/// Pointer distance to strokes (relative to the slide height) within which the eraser removes
+/// them
+const ERASER_RADIUS: f32 = 0.015;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InkTool {
+    Pen,
+    Highlighter,
+    Eraser,
+}
+
+impl InkTool {
+    /// Width relative to the height of the slide
+    pub(crate) fn width(&self) -> f32 {
+        match self {
+            InkTool::Pen => 0.004,
+            InkTool::Highlighter => 0.025,
+            InkTool::Eraser => 2.0 * ERASER_RADIUS,
+        }
+    }
+
+    pub(crate) fn color(&self) -> Color32 {
+        match self {
+            InkTool::Pen => Color32::from_rgb(220, 30, 30),
+            InkTool::Highlighter => Color32::from_rgba_unmultiplied(255, 220, 0, 100),
+            InkTool::Eraser => Color32::from_white_alpha(60),
+        }
+    }
+}
+
+/// Freehand line drawn onto a slide.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InkStroke {
+    pub tool: InkTool,
+    /// Points `[x, y]` in percent of the slide, like the `pos` of a video
+    pub points: Vec<[f32; 2]>,
+}
+
+impl InkStroke {
+    /// Points on the slide shown at `slide_rect`.
+    fn points_in(&self, slide_rect: Rect) -> Vec<Pos2> {
+        self.points
+            .iter()
+            .map(|[x, y]| {
+                slide_rect.min
+                    + egui::vec2(
+                        SizeEntry::Percent(*x).calc_size(slide_rect.width()),
+                        SizeEntry::Percent(*y).calc_size(slide_rect.height()),
+                    )
+            })
+            .collect()
+    }
+
+    fn stroke_in(&self, slide_rect: Rect) -> Stroke {
+        Stroke::new(self.tool.width() * slide_rect.height(), self.tool.color())
+    }
+}
+
+/// Ink annotations of a document, kept in a sidecar file next to it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ink {
+    /// Strokes by the index of their slide
+    pub slides: BTreeMap<usize, Vec<InkStroke>>,
+}
+
+impl Ink {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("{}: {}", path.to_string_lossy(), e))?;
+        serde_json::from_str(&content).map_err(|e| format!("{}: {}", path.to_string_lossy(), e))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| format!("{}: {}", path.to_string_lossy(), e))
+    }
+}
+
+/// Sidecar file of the ink annotations of a document, e.g. `talk.pdf.ink.json`.
+pub fn ink_path(pdf_path: &Path) -> PathBuf {
+    let mut file_name = pdf_path.file_name().unwrap_or_default().to_owned();
+    file_name.push(".ink.json");
+    pdf_path.with_file_name(file_name)
+}
+
+/// Writes a copy of the PDF with the ink of the sidecar file drawn onto its pages.
+pub fn burn_in_ink(pdf_path: &Path, ink_path: &Path, output_path: &Path) -> Result<(), String> {
+    let ink = Ink::read(ink_path)?;
+    PdfRenderer::new(PdfRenderConfig::new(), pdf_path.to_path_buf())
+        .save_with_ink(&ink, output_path)
+}
+
+/// Distance of `pos` to the line segment from `a` to `b`.
+fn distance_to_segment(pos: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_sq() > 0.0 {
+        ((pos - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    pos.distance(a + t * ab)
+}
+
+/// Draws ink onto the slides with the selected tool and shows the strokes of the current slide.
+pub struct InkLayer {
+    pub ink: Ink,
+    /// `None` while not drawing
+    pub tool: Option<InkTool>,
+    /// Stroke being drawn
+    drawing: Option<InkStroke>,
+    /// Sidecar file the ink is saved to on every change
+    path: Option<PathBuf>,
+    /// Strokes of slides before their changes, the last change at the end
+    undo_stack: Vec<(usize, Vec<InkStroke>)>,
+}
+
+impl InkLayer {
+    /// Continues with the ink of the sidecar file, if it exists.
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let ink = match &path {
+            Some(path) if path.exists() => Ink::read(path).unwrap_or_else(|e| {
+                println!("Could not read the ink: {}", e);
+                Ink::default()
+            }),
+            _ => Ink::default(),
+        };
+        Self {
+            ink,
+            tool: None,
+            drawing: None,
+            path,
+            undo_stack: Vec::new(),
+        }
+    }
+
+    /// Selects the tool, or stops drawing if it is already selected.
+    pub fn toggle_tool(&mut self, tool: InkTool) {
+        self.tool = (self.tool != Some(tool)).then_some(tool);
+        self.drawing = None;
+    }
+
+    pub fn clear(&mut self, page_idx: usize) {
+        if let Some(strokes) = self.ink.slides.remove(&page_idx) {
+            self.push_undo(page_idx, strokes);
+            self.save();
+        }
+    }
+
+    fn add_stroke(&mut self, page_idx: usize, stroke: InkStroke) {
+        let strokes = self.ink.slides.entry(page_idx).or_default();
+        let previous = strokes.clone();
+        strokes.push(stroke);
+        self.push_undo(page_idx, previous);
+        self.save();
+    }
+
+    fn push_undo(&mut self, page_idx: usize, strokes: Vec<InkStroke>) {
+        if self.undo_stack.len() == UNDO_DEPTH {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push((page_idx, strokes));
+    }
+
+    /// Reverts the last change (stroke, erasing or clearing) of the slide.
+    pub fn undo(&mut self, page_idx: usize) {
+        let Some(idx) = self
+            .undo_stack
+            .iter()
+            .rposition(|(undo_page_idx, _)| *undo_page_idx == page_idx)
+        else {
+            return;
+        };
+        let (_, strokes) = self.undo_stack.remove(idx);
+        if strokes.is_empty() {
+            self.ink.slides.remove(&page_idx);
+        } else {
+            self.ink.slides.insert(page_idx, strokes);
+        }
+        self.drawing = None;
+        self.save();
+    }
+
+    fn save(&self) {
+        if let Some(path) = &self.path {
+            if let Err(e) = self.ink.write(path) {
+                println!("Could not save the ink: {}", e);
+            }
+        }
+    }
+
+    /// Draws with the selected tool and paints the strokes of the slide shown at `slide_rect`.
+    pub fn ui(&mut self, ui: &mut egui::Ui, page_idx: usize, slide_rect: Rect) {
+        if let Some(tool) = self.tool {
+            self.handle_input(ui, tool, page_idx, slide_rect);
+        }
+        let painter = ui.painter_at(slide_rect);
+        let strokes = self.ink.slides.get(&page_idx).into_iter().flatten();
+        for stroke in strokes.chain(&self.drawing) {
+            if stroke.tool == InkTool::Eraser {
+                continue;
+            }
+            painter.line(stroke.points_in(slide_rect), stroke.stroke_in(slide_rect));
+        }
+    }
+
+    fn handle_input(
+        &mut self,
+        ui: &mut egui::Ui,
+        tool: InkTool,
+        page_idx: usize,
+        slide_rect: Rect,
+    ) {
+        // on top of the videos, so that drawing does not control them
+        let response = ui.interact(slide_rect, egui::Id::new("ink"), egui::Sense::drag());
+        ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+        let Some(pos) = response.interact_pointer_pos().or(response.hover_pos()) else {
+            return;
+        };
+        if tool == InkTool::Eraser {
+            let radius = ERASER_RADIUS * slide_rect.height();
+            ui.painter()
+                .circle_filled(pos, radius, InkTool::Eraser.color());
+            if !response.dragged() {
+                return;
+            }
+            let Some(strokes) = self.ink.slides.get_mut(&page_idx) else {
+                return;
+            };
+            let previous = strokes.clone();
+            strokes.retain(|stroke| {
+                !stroke
+                    .points_in(slide_rect)
+                    .windows(2)
+                    .any(|segment| distance_to_segment(pos, segment[0], segment[1]) <= radius)
+            });
+            if strokes.len() != previous.len() {
+                self.push_undo(page_idx, previous);
+                self.save();
+            }
+            return;
+        }
+
+        if response.dragged() {
+            let relative = (pos - slide_rect.min) / slide_rect.size() * 100.0;
+            let drawing = self.drawing.get_or_insert_with(|| InkStroke {
+                tool,
+                points: Vec::new(),
+            });
+            drawing.points.push([relative.x, relative.y]);
+        }
+        if response.drag_stopped() {
+            // single clicks leave no stroke
+            if let Some(drawing) = self.drawing.take().filter(|stroke| stroke.points.len() > 1) {
+                self.add_stroke(page_idx, drawing);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scale_strokes_with_slide() {
+        let stroke = InkStroke {
+            tool: InkTool::Pen,
+            points: vec![[0.0, 0.0], [50.0, 25.0]],
+        };
+        let slide_rect = Rect::from_min_size(egui::pos2(10.0, 20.0), egui::vec2(200.0, 100.0));
+        assert_eq!(
+            stroke.points_in(slide_rect),
+            vec![egui::pos2(10.0, 20.0), egui::pos2(110.0, 45.0)]
+        );
+        assert_eq!(
+            ink_path(Path::new("talks/talk.pdf")),
+            PathBuf::from("talks/talk.pdf.ink.json")
+        );
+    }
+
+    #[test]
+    fn undo_changes_of_slide() {
+        let stroke = |x| InkStroke {
+            tool: InkTool::Pen,
+            points: vec![[x, 0.0], [x, 10.0]],
+        };
+        let mut layer = InkLayer::new(None);
+        layer.add_stroke(1, stroke(0.0));
+        layer.add_stroke(1, stroke(5.0));
+        layer.add_stroke(2, stroke(0.0));
+        layer.clear(1);
+        assert_eq!(layer.ink.slides.get(&1), None);
+
+        layer.undo(1);
+        assert_eq!(layer.ink.slides[&1], vec![stroke(0.0), stroke(5.0)]);
+        layer.undo(1);
+        assert_eq!(layer.ink.slides[&1], vec![stroke(0.0)]);
+        layer.undo(1);
+        layer.undo(1);
+        assert_eq!(layer.ink.slides.get(&1), None);
+        // other slides keep their ink
+        assert_eq!(layer.ink.slides[&2], vec![stroke(0.0)]);
+    }
+}
@@ -0,0 +1,158 @@
+use egui::Rect;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{PosRequest, SizeRequest};
+
+/// How the values change between two keyframes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Starts slowly and speeds up
+    EaseIn,
+    /// Starts fast and slows down
+    EaseOut,
+    EaseInOut,
+    /// Jumps to the values of the next keyframe once it is reached
+    Step,
+}
+
+impl Easing {
+    /// Maps the progress `t` between two keyframes (from 0 to 1) to the progress of the values.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+/// Values of an overlay at a point in time, missing ones are taken from its entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframe {
+    /// Seconds since the start of the animation
+    pub time: f32,
+    pub pos: Option<PosRequest>,
+    pub size: Option<SizeRequest>,
+    pub opacity: Option<f32>,
+    /// Easing of the transition towards this keyframe
+    pub easing: Easing,
+}
+
+/// Animation of an overlay along keyframes, started when its slide is shown or by a key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    /// Key starting the animation, `None` to start it when the slide is shown
+    pub trigger: Option<egui::Key>,
+    /// Sorted by time
+    pub keyframes: Vec<Keyframe>,
+}
+
+/// Where and how opaque an overlay is painted at a point of its animation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnimatedState {
+    pub rect: Rect,
+    pub opacity: f32,
+}
+
+impl AnimatedState {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            rect: Rect::from_min_max(
+                self.rect.min.lerp(other.rect.min, t),
+                self.rect.max.lerp(other.rect.max, t),
+            ),
+            opacity: self.opacity + (other.opacity - self.opacity) * t,
+        }
+    }
+}
+
+impl Animation {
+    /// Seconds until the last keyframe is reached.
+    pub fn duration(&self) -> f32 {
+        self.keyframes
+            .last()
+            .map(|keyframe| keyframe.time)
+            .unwrap_or_default()
+    }
+
+    /// State `time` seconds after the start, `state_of` gives the state at a single keyframe.
+    ///
+    /// Before the first and after the last keyframe, their states are held.
+    pub fn state_at(
+        &self,
+        time: f32,
+        state_of: impl Fn(&Keyframe) -> AnimatedState,
+    ) -> Option<AnimatedState> {
+        match self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time)
+        {
+            None => self.keyframes.last().map(state_of),
+            Some(0) => Some(state_of(&self.keyframes[0])),
+            Some(idx) => {
+                let from = &self.keyframes[idx - 1];
+                let to = &self.keyframes[idx];
+                let t = (time - from.time) / (to.time - from.time);
+                Some(state_of(from).lerp(&state_of(to), to.easing.apply(t)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn interpolate_between_keyframes() {
+        let keyframe = |time: f32, opacity: f32, easing: Easing| Keyframe {
+            time,
+            pos: None,
+            size: None,
+            opacity: Some(opacity),
+            easing,
+        };
+        let animation = Animation {
+            trigger: None,
+            keyframes: vec![
+                keyframe(1.0, 0.0, Easing::Linear),
+                keyframe(2.0, 1.0, Easing::Linear),
+                keyframe(3.0, 0.0, Easing::Step),
+            ],
+        };
+        let opacity_at = |time: f32| {
+            animation
+                .state_at(time, |keyframe| AnimatedState {
+                    rect: Rect::ZERO,
+                    opacity: keyframe.opacity.unwrap(),
+                })
+                .unwrap()
+                .opacity
+        };
+        assert_eq!(opacity_at(0.0), 0.0);
+        assert_eq!(opacity_at(1.5), 0.5);
+        assert_eq!(opacity_at(2.5), 1.0);
+        assert_eq!(opacity_at(4.0), 0.0);
+        assert_eq!(animation.duration(), 3.0);
+    }
+}
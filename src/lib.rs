@@ -1,15 +1,54 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+mod alpha_video;
 mod app;
+mod appearance;
+pub use appearance::{Appearance, Crop, Fit};
+mod audio;
+mod auto_advance;
+pub use auto_advance::AutoAdvance;
+mod chroma_key;
+pub use chroma_key::ChromaKey;
 use std::path::PathBuf;
 
-pub use app::TemplateApp;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub use app::{TemplateApp, ViewerOptions};
+
+mod bundle;
+pub use bundle::{is_bundle, pack, unpack, Bundle, BUNDLE_EXTENSION};
 
 mod config;
 pub use config::Config;
+mod editor;
+mod history;
+mod image_player;
+mod image_sequence;
+mod ink;
+pub use ink::{burn_in_ink, ink_path};
+mod keyframes;
+pub use keyframes::{Animation, Easing, Keyframe};
+mod links;
+mod outline;
+mod overlay;
+pub use overlay::{register_overlay, SlideOverlay};
 mod pdf;
+mod pdf_media;
+mod pointer;
+mod search;
 mod slides;
+mod timer;
+pub use timer::TimerSettings;
+mod transition;
+pub use transition::{Transition, TransitionKind, Transitions};
+mod validate;
+pub use validate::{validate, Problem};
 mod video;
+mod zoom;
+
+/// Prefix of a `video_path` referring to a file attached to the PDF, e.g. `attachment:demo.mp4`
+pub const ATTACHMENT_PREFIX: &str = "attachment:";
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum SizeEntry {
@@ -75,11 +114,63 @@ impl Default for SizeRequest {
     }
 }
 
+/// Kind of media of an entry, deciding how it is decoded.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaType {
+    /// Any format ffmpeg can decode, or an image sequence (a directory of frames or a glob
+    /// such as `frames/frame_*.png`)
+    #[default]
+    Video,
+    /// Static image (PNG, JPEG, ...), decoded without ffmpeg
+    Image,
+    /// Animated GIF, APNG or WebP, decoded without ffmpeg
+    AnimatedImage,
+    /// Video with an alpha channel (e.g. VP9 in WebM or ProRes 4444), decoded by the `ffmpeg`
+    /// command line tool and played without sound
+    AlphaVideo,
+    /// Kind of overlay registered via [`register_overlay`]
+    #[serde(untagged)]
+    Custom(String),
+}
+
+impl MediaType {
+    /// Guesses the lightest fitting media type by the extension of the file.
+    pub fn from_path(path: &std::path::Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("gif" | "apng" | "webp") => MediaType::AnimatedImage,
+            Some("png" | "jpg" | "jpeg" | "bmp" | "tif" | "tiff") => MediaType::Image,
+            _ => MediaType::Video,
+        }
+    }
+}
+
+/// Audio track playing across a range of slides, independent of their videos.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioEntry {
+    pub audio_path: PathBuf,
+    /// Slides (inclusive) on which the track keeps playing
+    pub slide_range: std::ops::RangeInclusive<usize>,
+    pub volume: f32,
+    /// Duration of the fade out once the range is left
+    pub fade_out: std::time::Duration,
+    pub looping: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct VideoEntry {
     pub slide_nums: Vec<usize>,
     pub video_path: PathBuf,
+    pub media_type: MediaType,
+    /// Frame rate of image sequences
+    pub fps: Option<f32>,
     // TODO: pos should _not_ be of type SizeRequest
     pub pos: PosRequest,
     pub size: SizeRequest,
+    pub appearance: Appearance,
+    pub animation: Option<Animation>,
 }
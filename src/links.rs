@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use egui::Rect;
+
+use crate::pdf::{LinkTarget, PdfLink};
+
+/// Makes the links of the PDF clickable: links to pages jump there, URLs are opened by the
+/// system after a confirmation.
+pub struct Links {
+    /// Links by the index of their slide
+    links: HashMap<usize, Vec<PdfLink>>,
+    /// URL waiting for the confirmation to be opened
+    pending_url: Option<String>,
+}
+
+impl Links {
+    pub fn new(links: HashMap<usize, Vec<PdfLink>>) -> Self {
+        Self {
+            links,
+            pending_url: None,
+        }
+    }
+
+    /// Whether the confirmation to open a URL is shown.
+    pub fn is_confirming(&self) -> bool {
+        self.pending_url.is_some()
+    }
+
+    /// Handles the links of the slide shown at `slide_rect`, returns the page to jump to.
+    pub fn ui(&mut self, ui: &mut egui::Ui, page_idx: usize, slide_rect: Rect) -> Option<usize> {
+        let mut jump_to = None;
+        for (idx, link) in self.links.get(&page_idx).into_iter().flatten().enumerate() {
+            let rect = Rect::from_min_max(
+                slide_rect.lerp_inside(link.rect.min.to_vec2()),
+                slide_rect.lerp_inside(link.rect.max.to_vec2()),
+            );
+            let response = ui
+                .interact(
+                    rect,
+                    egui::Id::new(("link", page_idx, idx)),
+                    egui::Sense::click(),
+                )
+                .on_hover_cursor(egui::CursorIcon::PointingHand);
+            let response = match &link.target {
+                LinkTarget::Page(target_idx) => {
+                    response.on_hover_text(format!("Slide {}", target_idx))
+                }
+                LinkTarget::Url(url) => response.on_hover_text(url),
+            };
+            if response.clicked() {
+                match &link.target {
+                    LinkTarget::Page(target_idx) => jump_to = Some(*target_idx),
+                    LinkTarget::Url(url) => self.pending_url = Some(url.clone()),
+                }
+            }
+        }
+        self.confirm(ui.ctx());
+        jump_to
+    }
+
+    /// Asks whether to open the pending URL.
+    fn confirm(&mut self, ctx: &egui::Context) {
+        let Some(url) = &self.pending_url else {
+            return;
+        };
+        let mut open = false;
+        let modal = egui::Modal::new(egui::Id::new("open_url")).show(ctx, |ui| {
+            ui.label("Open the link in the browser?");
+            ui.monospace(url);
+            ui.horizontal(|ui| {
+                open = ui.button("Open").clicked();
+                ui.button("Cancel").clicked()
+            })
+            .inner
+        });
+        if open {
+            ctx.open_url(egui::OpenUrl::new_tab(url));
+        }
+        if open || modal.inner || modal.should_close() {
+            self.pending_url = None;
+        }
+    }
+}
@@ -2,40 +2,280 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
 use bewegtbild::Config;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use notify::{Event, RecursiveMode, Watcher};
 use std::fs;
 use std::path::PathBuf;
 use std::thread;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    #[clap(help = "PDF file to view")]
-    pdf_path: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[clap(required = true, help = "PDF file (or .bwb bundle) to view")]
+    pdf_path: Option<PathBuf>,
 
     #[clap(short, long, help = "Configuration file with video annotations")]
     config: Option<PathBuf>,
 
     #[clap(long, help = "Reload the configuration on change")]
     reload: bool,
+
+    #[clap(
+        long,
+        help = "Run unattended: advance and loop automatically, no manual navigation, no cursor"
+    )]
+    kiosk: bool,
+
+    #[clap(
+        long,
+        value_name = "FILE",
+        help = "Record the time spent on each slide to a .json or .csv file"
+    )]
+    rehearse: Option<PathBuf>,
+
+    #[clap(long, conflicts_with = "windowed", help = "Start in fullscreen")]
+    fullscreen: bool,
+
+    #[clap(
+        long,
+        value_name = "WxH",
+        value_parser = parse_size,
+        help = "Size of the window, e.g. 1280x720"
+    )]
+    windowed: Option<[f32; 2]>,
+
+    #[clap(
+        long,
+        value_name = "N",
+        conflicts_with = "position",
+        help = "Monitor to open the window on, counting from 0: the primary one first, the others from left to right"
+    )]
+    monitor: Option<usize>,
+
+    #[clap(
+        long,
+        value_name = "X,Y",
+        value_parser = parse_position,
+        help = "Position of the window, e.g. 1920,0 for a projector right of a 1920 pixels wide screen"
+    )]
+    position: Option<[f32; 2]>,
+
+    #[clap(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        help = "Slide to start at"
+    )]
+    start_slide: usize,
+}
+
+/// Parses two numbers separated by `separator`, e.g. `1280x720`.
+fn parse_pair(value: &str, separator: char) -> Result<[f32; 2], String> {
+    let (a, b) = value
+        .split_once(separator)
+        .ok_or_else(|| format!("expected two numbers separated by `{}`", separator))?;
+    let parse = |number: &str| {
+        let number = number.trim().parse::<f32>().map_err(|e| e.to_string())?;
+        if number.is_finite() {
+            Ok(number)
+        } else {
+            Err(format!("`{}` is not a finite number", number))
+        }
+    };
+    Ok([parse(a)?, parse(b)?])
+}
+
+fn parse_size(value: &str) -> Result<[f32; 2], String> {
+    let size = parse_pair(value, 'x')?;
+    if size.iter().any(|length| *length <= 0.0) {
+        return Err("width and height have to be positive".to_owned());
+    }
+    Ok(size)
+}
+
+fn parse_position(value: &str) -> Result<[f32; 2], String> {
+    parse_pair(value, ',')
+}
+
+/// Origin of the monitor `n` in points, counting from 0: the primary monitor first, the others
+/// from left to right.
+#[cfg(not(target_arch = "wasm32"))]
+fn monitor_position(n: usize) -> Result<[f32; 2], String> {
+    let mut monitors = display_info::DisplayInfo::all().map_err(|e| e.to_string())?;
+    monitors.sort_by_key(|monitor| (!monitor.is_primary, monitor.x, monitor.y));
+    let monitor = monitors.get(n).ok_or_else(|| {
+        format!(
+            "monitor {} does not exist, there are {} monitors (counting from 0)",
+            n,
+            monitors.len()
+        )
+    })?;
+    let scale_factor = if monitor.scale_factor > 0.0 {
+        monitor.scale_factor
+    } else {
+        1.0
+    };
+    Ok([
+        monitor.x as f32 / scale_factor,
+        monitor.y as f32 / scale_factor,
+    ])
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[clap(about = "Check a configuration against a PDF without viewing it")]
+    Validate {
+        #[clap(help = "PDF file the configuration belongs to")]
+        pdf_path: PathBuf,
+
+        #[clap(short, long, help = "Configuration file with video annotations")]
+        config: PathBuf,
+    },
+    #[clap(about = "Print the JSON Schema of the configuration file")]
+    Schema,
+    #[clap(about = "Pack PDF, configuration and videos into a single .bwb bundle")]
+    Pack {
+        #[clap(help = "PDF file to pack")]
+        pdf_path: PathBuf,
+
+        #[clap(short, long, help = "Configuration file with video annotations")]
+        config: Option<PathBuf>,
+
+        #[clap(short, long, help = "Bundle to write, e.g. talk.bwb")]
+        output: PathBuf,
+    },
+    #[clap(about = "Write a copy of the PDF with the ink annotations drawn onto its pages")]
+    Export {
+        #[clap(help = "PDF file (or .bwb bundle) the ink belongs to")]
+        pdf_path: PathBuf,
+
+        #[clap(long, help = "Ink annotations (defaults to <PDF>.ink.json)")]
+        ink: Option<PathBuf>,
+
+        #[clap(short, long, help = "PDF file to write")]
+        output: PathBuf,
+    },
+}
+
+fn read_config(config_path: &PathBuf) -> Config {
+    serde_json::from_str(&fs::read_to_string(config_path).expect("Could not read config file."))
+        .expect("The format of the config file is wrong.")
+}
+
+/// Runs the `validate` command, exits with a non-zero code on problems.
+fn validate(pdf_path: PathBuf, config_path: PathBuf) {
+    let config_str = fs::read_to_string(&config_path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", config_path.to_string_lossy(), e);
+        std::process::exit(1)
+    });
+    let config: Config = serde_json::from_str(&config_str).unwrap_or_else(|e| {
+        eprintln!(
+            "{}:{}:{}: {}",
+            config_path.to_string_lossy(),
+            e.line(),
+            e.column(),
+            e
+        );
+        std::process::exit(1)
+    });
+    let problems = bewegtbild::validate(&pdf_path, config);
+    for problem in &problems {
+        eprintln!("{}: {}", config_path.to_string_lossy(), problem);
+    }
+    if !problems.is_empty() {
+        eprintln!("Found {} problem(s).", problems.len());
+        std::process::exit(1)
+    }
+    println!("{}: OK", config_path.to_string_lossy());
 }
 
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result {
-    use bewegtbild::VideoEntry;
     use notify::event::ModifyKind;
     use std::sync::mpsc;
 
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
     let args = Args::parse();
+    match args.command {
+        Some(Command::Validate { pdf_path, config }) => {
+            validate(pdf_path, config);
+            return Ok(());
+        }
+        Some(Command::Schema) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&Config::json_schema())
+                    .expect("Could not serialize the schema.")
+            );
+            return Ok(());
+        }
+        Some(Command::Pack {
+            pdf_path,
+            config,
+            output,
+        }) => {
+            let config = config.as_ref().map(read_config).unwrap_or_default();
+            if let Err(e) = bewegtbild::pack(&pdf_path, config, &output) {
+                eprintln!("Could not pack the bundle: {}", e);
+                std::process::exit(1)
+            }
+            println!("Packed {}", output.to_string_lossy());
+            return Ok(());
+        }
+        Some(Command::Export {
+            pdf_path,
+            ink,
+            output,
+        }) => {
+            let ink_path = ink.unwrap_or_else(|| bewegtbild::ink_path(&pdf_path));
+            let pdf_path = if bewegtbild::is_bundle(&pdf_path) {
+                bewegtbild::unpack(&pdf_path)
+                    .unwrap_or_else(|e| panic!("Could not open the bundle: {}", e))
+                    .pdf_path
+            } else {
+                pdf_path
+            };
+            if let Err(e) = bewegtbild::burn_in_ink(&pdf_path, &ink_path, &output) {
+                eprintln!("Could not export the PDF: {}", e);
+                std::process::exit(1)
+            }
+            println!("Exported {}", output.to_string_lossy());
+            return Ok(());
+        }
+        None => {}
+    }
+    let pdf_path = args.pdf_path.expect("A PDF file is required.");
+    let viewer_options = bewegtbild::ViewerOptions {
+        kiosk: args.kiosk,
+        rehearsal_path: args.rehearse.clone(),
+        // next to the PDF or bundle the user opened, not to an extracted copy
+        ink_path: Some(bewegtbild::ink_path(&pdf_path)),
+        start_slide: args.start_slide,
+    };
+    let (pdf_path, bundle_config) = if bewegtbild::is_bundle(&pdf_path) {
+        let bundle = bewegtbild::unpack(&pdf_path)
+            .unwrap_or_else(|e| panic!("Could not open the bundle: {}", e));
+        (bundle.pdf_path, Some(bundle.config))
+    } else {
+        (pdf_path, None)
+    };
+    let config_path = args.config.clone();
     let mut ui_rx_opt = None;
     if args.reload {
         if let Some(config_path) = &args.config {
             let config_path_abs = std::path::absolute(config_path.clone()).unwrap();
             let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
-            let (ui_tx, ui_rx) = mpsc::channel::<Vec<VideoEntry>>();
+            let (ui_tx, ui_rx) = mpsc::channel::<Config>();
             ui_rx_opt = Some(ui_rx);
 
             thread::spawn(move || {
@@ -63,7 +303,7 @@ fn main() -> eframe::Result {
                                                 .expect("Could not read config file."),
                                         );
                                     match config {
-                                        Ok(config) => ui_tx.send(config.video_entries()).unwrap(),
+                                        Ok(config) => ui_tx.send(config).unwrap(),
                                         // TODO: add color, make it pretty?
                                         Err(e) => println!("{}", e),
                                     }
@@ -76,23 +316,36 @@ fn main() -> eframe::Result {
             });
         }
     }
-    let config = match &args.config {
-        Some(config_path) => serde_json::from_str(
-            &fs::read_to_string(config_path).expect("Could not read config file."),
-        )
-        .expect("The format of the config file is wrong."),
-        None => Config::default(),
+    // an explicit configuration overrides the one of the bundle
+    let config = match (&args.config, bundle_config) {
+        (Some(config_path), _) => read_config(config_path),
+        (None, Some(bundle_config)) => bundle_config,
+        (None, None) => Config::default(),
     };
 
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(args.windowed.unwrap_or([400.0, 300.0]))
+        .with_min_inner_size([300.0, 220.0])
+        // booths and lobby screens show nothing but the slides
+        .with_fullscreen(args.fullscreen || args.kiosk)
+        .with_icon(
+            // NOTE: Adding an icon is optional
+            eframe::icon_data::from_png_bytes(&include_bytes!("../assets/icon-256.png")[..])
+                .expect("Failed to load icon"),
+        );
+    // fullscreen covers the monitor the window has been placed on
+    let position = match args.monitor {
+        Some(monitor) => Some(monitor_position(monitor).unwrap_or_else(|e| {
+            eprintln!("Could not place the window on the monitor: {}", e);
+            std::process::exit(1)
+        })),
+        None => args.position,
+    };
+    if let Some(position) = position {
+        viewport = viewport.with_position(position);
+    }
     let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([400.0, 300.0])
-            .with_min_inner_size([300.0, 220.0])
-            .with_icon(
-                // NOTE: Adding an icon is optional
-                eframe::icon_data::from_png_bytes(&include_bytes!("../assets/icon-256.png")[..])
-                    .expect("Failed to load icon"),
-            ),
+        viewport,
         ..Default::default()
     };
     eframe::run_native(
@@ -101,14 +354,31 @@ fn main() -> eframe::Result {
         Box::new(|cc| {
             Ok(Box::new(bewegtbild::TemplateApp::new(
                 cc,
-                args.pdf_path,
-                config.video_entries(),
+                pdf_path,
+                config,
+                config_path,
                 ui_rx_opt,
+                viewer_options,
             )))
         }),
     )
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_window_arguments() {
+        assert_eq!(parse_size("1280x720"), Ok([1280.0, 720.0]));
+        assert_eq!(parse_position("-1920, 0"), Ok([-1920.0, 0.0]));
+        assert!(parse_size("-5x0").is_err());
+        assert!(parse_size("NaNxinf").is_err());
+        assert!(parse_size("1280").is_err());
+        assert!(parse_position("inf,0").is_err());
+    }
+}
+
 // When compiling to web using trunk:
 #[cfg(target_arch = "wasm32")]
 fn main() {
@@ -0,0 +1,110 @@
+use crate::pdf::OutlineEntry;
+
+/// Sidebar listing the bookmarks of the PDF, e.g. the sections of a beamer deck.
+pub struct Outline {
+    entries: Vec<OutlineEntry>,
+    pub visible: bool,
+}
+
+impl Outline {
+    pub fn new(entries: Vec<OutlineEntry>) -> Self {
+        Self {
+            entries,
+            visible: false,
+        }
+    }
+
+    /// Index of the entry the slide belongs to, i.e. the last one starting at or before it.
+    fn current(&self, page_idx: usize) -> Option<usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.page_idx <= page_idx)
+            .max_by_key(|(idx, entry)| (entry.page_idx, *idx))
+            .map(|(idx, _)| idx)
+    }
+
+    /// Slides starting the top level entries, e.g. the sections.
+    fn section_starts(&self) -> impl Iterator<Item = usize> + '_ {
+        let top_level = self.entries.iter().map(|entry| entry.depth).min();
+        self.entries
+            .iter()
+            .filter(move |entry| Some(entry.depth) == top_level)
+            .map(|entry| entry.page_idx)
+    }
+
+    /// Start of the next section, if any.
+    pub fn next_section(&self, page_idx: usize) -> Option<usize> {
+        self.section_starts()
+            .filter(|start| *start > page_idx)
+            .min()
+    }
+
+    /// Start of the current section, or of the previous one when already there.
+    pub fn previous_section(&self, page_idx: usize) -> Option<usize> {
+        self.section_starts()
+            .filter(|start| *start < page_idx)
+            .max()
+    }
+
+    /// Shows the sidebar if visible, returns the slide of a clicked entry.
+    ///
+    /// Has to be called before the central panel.
+    pub fn ui(&self, ctx: &egui::Context, page_idx: usize) -> Option<usize> {
+        if !self.visible {
+            return None;
+        }
+        let current = self.current(page_idx);
+        let mut jump_to = None;
+        egui::SidePanel::left("outline")
+            .default_width(220.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if self.entries.is_empty() {
+                        ui.label("The PDF has no outline");
+                    }
+                    for (idx, entry) in self.entries.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.add_space(16.0 * entry.depth as f32);
+                            let response = ui.selectable_label(current == Some(idx), &entry.title);
+                            if response.clicked() {
+                                jump_to = Some(entry.page_idx);
+                                // keys are for the slides, not for the sidebar
+                                response.surrender_focus();
+                            }
+                        });
+                    }
+                });
+            });
+        jump_to
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn navigate_sections() {
+        let entry = |title: &str, depth, page_idx| OutlineEntry {
+            title: title.to_owned(),
+            depth,
+            page_idx,
+        };
+        let outline = Outline::new(vec![
+            entry("Introduction", 0, 1),
+            entry("Motivation", 1, 2),
+            entry("Results", 0, 5),
+            entry("Plots", 1, 5),
+            entry("Backup", 0, 9),
+        ]);
+        assert_eq!(outline.current(0), None);
+        assert_eq!(outline.current(3), Some(1));
+        assert_eq!(outline.current(5), Some(3));
+        assert_eq!(outline.next_section(3), Some(5));
+        assert_eq!(outline.next_section(9), None);
+        assert_eq!(outline.previous_section(7), Some(5));
+        assert_eq!(outline.previous_section(5), Some(1));
+        assert_eq!(outline.previous_section(1), None);
+    }
+}
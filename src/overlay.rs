@@ -0,0 +1,88 @@
+use std::{
+    collections::HashMap,
+    sync::{OnceLock, RwLock},
+};
+
+use crate::{
+    alpha_video::AlphaVideoPlayer,
+    image_player::ImagePlayer,
+    image_sequence::{is_image_sequence, ImageSequencePlayer},
+    video::VideoPlayer,
+    MediaType, VideoEntry,
+};
+
+/// Something rendered on top of a slide, e.g. a video.
+///
+/// Implement it to add own kinds of overlays and make them known via [`register_overlay`].
+pub trait SlideOverlay {
+    /// Opens the media, called once the slide of the overlay is shown.
+    fn init(&mut self, ctx: &egui::Context, media_path: &str);
+
+    fn start(&mut self);
+
+    /// Renders the overlay into the given rect of the slide.
+    fn render(&mut self, ui: &mut egui::Ui, rect: egui::Rect) -> Option<egui::Response>;
+
+    /// Natural size of the overlay, used to fill in automatic widths and heights.
+    ///
+    /// Overlays without a size (yet) are not rendered. Called every frame while the slide is
+    /// shown, e.g. to pick up media loaded in the background since `init`.
+    fn size(&self) -> Option<egui::Vec2>;
+
+    fn pause(&mut self) {}
+
+    /// Whether the overlay has been initialised and not destroyed since.
+    fn is_playing(&self) -> bool;
+
+    /// Whether the overlay has played to its end at least once, e.g. to advance the slides
+    /// automatically. Overlays without an end (e.g. still images) always have.
+    fn is_finished(&self) -> bool {
+        true
+    }
+
+    /// Releases the media, called once the slide of the overlay is left.
+    fn destroy(&mut self);
+}
+
+type OverlayFactory = Box<dyn Fn() -> Box<dyn SlideOverlay> + Send + Sync>;
+
+fn registered_overlays() -> &'static RwLock<HashMap<String, OverlayFactory>> {
+    static OVERLAYS: OnceLock<RwLock<HashMap<String, OverlayFactory>>> = OnceLock::new();
+    OVERLAYS.get_or_init(Default::default)
+}
+
+/// Registers an own kind of overlay, used for entries with `"type": "<kind>"`.
+///
+/// Has to be called before the slides are opened. The built-in kinds (`video`, `image`,
+/// `animated_image` and `alpha_video`) cannot be replaced.
+pub fn register_overlay<F>(kind: &str, factory: F)
+where
+    F: Fn() -> Box<dyn SlideOverlay> + Send + Sync + 'static,
+{
+    registered_overlays()
+        .write()
+        .unwrap()
+        .insert(kind.to_owned(), Box::new(factory));
+}
+
+pub(crate) fn is_registered(kind: &str) -> bool {
+    registered_overlays().read().unwrap().contains_key(kind)
+}
+
+/// Creates the overlay for the media type of the entry, `None` for unknown kinds.
+pub(crate) fn create_overlay(entry: &VideoEntry) -> Option<Box<dyn SlideOverlay>> {
+    match &entry.media_type {
+        MediaType::Video if is_image_sequence(&entry.video_path) => {
+            Some(Box::new(ImageSequencePlayer::new(entry.fps)))
+        }
+        MediaType::Video => Some(Box::new(VideoPlayer::new())),
+        MediaType::AlphaVideo => Some(Box::new(AlphaVideoPlayer::new())),
+        MediaType::Image => Some(Box::new(ImagePlayer::new(false))),
+        MediaType::AnimatedImage => Some(Box::new(ImagePlayer::new(true))),
+        MediaType::Custom(kind) => registered_overlays()
+            .read()
+            .unwrap()
+            .get(kind)
+            .map(|factory| factory()),
+    }
+}
@@ -1,8 +1,204 @@
-use std::{fs, path::PathBuf};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    env, fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 
 use egui::ColorImage;
 use image::DynamicImage;
 use pdfium_render::prelude::*;
+use percent_encoding::percent_decode_str;
+
+use crate::{
+    ink::{Ink, InkTool},
+    pdf_media::media_file_specs,
+    Appearance, MediaType, PosRequest, SizeEntry, SizeRequest, VideoEntry, ATTACHMENT_PREFIX,
+};
+
+/// File extensions of media that annotations may reference
+const MEDIA_EXTENSIONS: [&str; 14] = [
+    "mp4", "m4v", "mkv", "webm", "mov", "avi", "ogv", "mpg", "mpeg", "wmv", "flv", "gif", "apng",
+    "webp",
+];
+
+/// Interprets the reference of an annotation (e.g. `file:clip.mp4`) as a path to a media file.
+fn media_path(reference: &str) -> Option<PathBuf> {
+    if reference.starts_with(ATTACHMENT_PREFIX) {
+        return Some(PathBuf::from(reference));
+    }
+    let path = match reference
+        .strip_prefix("file://")
+        .or_else(|| reference.strip_prefix("file:"))
+    {
+        // URIs escape spaces and the like, e.g. `file:my%20clip.mp4`
+        Some(uri) => percent_decode_str(uri.trim())
+            .decode_utf8()
+            .ok()?
+            .into_owned(),
+        None => reference.trim().to_owned(),
+    };
+    if path.contains("://") {
+        return None;
+    }
+    let path = PathBuf::from(path);
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    MEDIA_EXTENSIONS
+        .contains(&extension.as_str())
+        .then_some(path)
+}
+
+/// Converts a rect in PDF coordinates (origin at the bottom left) to a rect relative to the page
+/// (origin at the top left, ranging from 0 to 1).
+fn relative_rect(rect: &PdfRect, page_size: (f32, f32)) -> egui::Rect {
+    let (width, height) = page_size;
+    egui::Rect::from_min_max(
+        egui::pos2(rect.left.value / width, 1.0 - rect.top.value / height),
+        egui::pos2(rect.right.value / width, 1.0 - rect.bottom.value / height),
+    )
+}
+
+/// Media file referenced by an annotation of the PDF.
+#[derive(Clone, Debug)]
+pub struct MediaAnnotation {
+    pub page_idx: usize,
+    /// Path as named by the annotation, relative to the PDF
+    pub named_path: PathBuf,
+    pub media_path: PathBuf,
+    /// Rect of the annotation relative to the page
+    pub rect: egui::Rect,
+}
+
+impl MediaAnnotation {
+    pub fn as_video_entry(&self) -> VideoEntry {
+        let percent = |value: f32| SizeEntry::Percent((value * 100.0).clamp(0.0, 100.0));
+        VideoEntry {
+            slide_nums: vec![self.page_idx],
+            video_path: self.media_path.clone(),
+            media_type: MediaType::from_path(&self.media_path),
+            fps: None,
+            pos: PosRequest {
+                width: percent(self.rect.min.x),
+                height: percent(self.rect.min.y),
+            },
+            size: SizeRequest::Size(percent(self.rect.width()), percent(self.rect.height())),
+            appearance: Appearance::default(),
+            animation: None,
+        }
+    }
+}
+
+/// Page of the document a destination, or else a go-to action, leads to.
+fn target_page(
+    destination: Option<PdfDestination<'_>>,
+    action: Option<&PdfAction<'_>>,
+) -> Option<usize> {
+    let destination = match destination {
+        Some(destination) => destination,
+        None => action?.as_local_destination_action()?.destination().ok()?,
+    };
+    destination
+        .page_index()
+        .ok()
+        .map(|page_idx| page_idx as usize)
+}
+
+/// Where a link of the PDF leads.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkTarget {
+    /// Index of a page of the same document
+    Page(usize),
+    Url(String),
+}
+
+/// Link annotation of the PDF.
+pub struct PdfLink {
+    /// Rect of the annotation relative to the page
+    pub rect: egui::Rect,
+    pub target: LinkTarget,
+}
+
+/// Slide containing a searched phrase.
+pub struct SearchHit {
+    pub page_idx: usize,
+    /// The first occurrence with some text around it
+    pub snippet: String,
+    /// Rects of all occurrences relative to the page
+    pub rects: Vec<egui::Rect>,
+}
+
+/// Characters of context on either side of a phrase found
+const SNIPPET_CONTEXT: usize = 30;
+
+/// The first occurrence of `query` in `text` (ignoring case) with some text around it, on a
+/// single line.
+fn snippet(text: &str, query: &str) -> Option<String> {
+    let chars: Vec<char> = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect();
+    let query: Vec<char> = query.trim().chars().collect();
+    let lowercase = |c: &char| c.to_lowercase().next().unwrap_or(*c);
+    let lower_chars: Vec<char> = chars.iter().map(lowercase).collect();
+    let lower_query: Vec<char> = query.iter().map(lowercase).collect();
+    if lower_query.is_empty() {
+        return None;
+    }
+    let start = lower_chars
+        .windows(lower_query.len())
+        .position(|window| window == lower_query)?;
+    let end = start + query.len();
+    let from = start.saturating_sub(SNIPPET_CONTEXT);
+    let to = (end + SNIPPET_CONTEXT).min(chars.len());
+    Some(format!(
+        "{}{}{}",
+        if from > 0 { "…" } else { "" },
+        chars[from..to].iter().collect::<String>(),
+        if to < chars.len() { "…" } else { "" }
+    ))
+}
+
+/// Bookmark of the outline of the PDF, e.g. a section of a beamer deck.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutlineEntry {
+    pub title: String,
+    /// 0 for the top level, e.g. sections, 1 for subsections and so on
+    pub depth: usize,
+    pub page_idx: usize,
+}
+
+/// Bookmarks visited at most, guarding against cyclic outlines
+const MAX_BOOKMARKS: usize = 10_000;
+
+/// Collects the bookmark, its children and its following siblings in document order.
+fn walk_outline(
+    bookmark: Option<PdfBookmark<'_>>,
+    depth: usize,
+    visited: &mut usize,
+    entries: &mut Vec<OutlineEntry>,
+) {
+    let mut bookmark = bookmark;
+    while let Some(current) = bookmark {
+        *visited += 1;
+        if *visited > MAX_BOOKMARKS {
+            return;
+        }
+        // bookmarks leading nowhere in the document still group their children
+        let page_idx = target_page(current.destination(), current.action().as_ref());
+        if let (Some(title), Some(page_idx)) = (current.title(), page_idx) {
+            entries.push(OutlineEntry {
+                title,
+                depth,
+                page_idx,
+            });
+        }
+        walk_outline(current.first_child(), depth + 1, visited, entries);
+        bookmark = current.next_sibling();
+    }
+}
 
 pub struct PdfRenderer {
     /// Instance to pdf rendering
@@ -15,40 +211,62 @@ pub struct PdfRenderer {
     pub num_pages: usize,
     /// Quick access to the rendering config
     pub render_config: PdfRenderConfig,
+    /// Directory to extract the attachments of this document to
+    attachment_dir: PathBuf,
+    /// Media annotations of the loaded document, scanned on first use
+    media_annotations: OnceLock<Vec<MediaAnnotation>>,
+}
+
+/// Temporary directory for the attachments, unique for the content of the document.
+fn attachment_dir(document_bytes: &[u8]) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    document_bytes.hash(&mut hasher);
+    env::temp_dir()
+        .join("bewegtbild")
+        .join(format!("{:016x}", hasher.finish()))
 }
 
-fn load_and_calc_pages(pdfium: &Pdfium, path: &PathBuf) -> (Vec<u8>, usize) {
+fn load_and_calc_pages(pdfium: &Pdfium, path: &PathBuf) -> Result<(Vec<u8>, usize), String> {
     let password = None;
-    let document_bytes = fs::read(path)
-        .unwrap_or_else(|_| panic!("Could not load pdf document at {}", path.to_string_lossy()));
+    let document_bytes = fs::read(path).map_err(|e| e.to_string())?;
     let document = pdfium
         .load_pdf_from_byte_slice(&document_bytes, password)
-        .expect("Loading documents failed, TODO: change function signature.");
+        .map_err(|e| e.to_string())?;
     let num_pages = document.pages().len() as usize;
     drop(document);
-    (document_bytes, num_pages)
+    Ok((document_bytes, num_pages))
 }
 
 impl PdfRenderer {
     pub fn new(render_config: PdfRenderConfig, pdf_path: PathBuf) -> Self {
+        let path = pdf_path.to_string_lossy().into_owned();
+        Self::try_new(render_config, pdf_path)
+            .unwrap_or_else(|e| panic!("Could not load pdf document at {}: {}", path, e))
+    }
+
+    /// Like [`PdfRenderer::new`], but returns an error for unreadable or corrupted documents and
+    /// if the Pdfium library cannot be found.
+    pub fn try_new(render_config: PdfRenderConfig, pdf_path: PathBuf) -> Result<Self, String> {
         #[cfg(not(feature = "static"))]
+        let bindings = Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path("./"))
+            .or_else(|_| Pdfium::bind_to_system_library());
+        #[cfg(feature = "static")]
+        let bindings = Pdfium::bind_to_statically_linked_library();
         let pdfium = Pdfium::new(
-            Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path("./"))
-                .or_else(|_| Pdfium::bind_to_system_library())
-                .expect("Could not access Pdfium bindings."),
+            bindings.map_err(|e| format!("Could not access Pdfium bindings: {:?}", e))?,
         );
-        #[cfg(feature = "static")]
-        let pdfium = Pdfium::new(Pdfium::bind_to_statically_linked_library().unwrap());
         println!("Loading PDF document...");
-        let (document_bytes, num_pages) = load_and_calc_pages(&pdfium, &pdf_path);
+        let (document_bytes, num_pages) = load_and_calc_pages(&pdfium, &pdf_path)?;
 
-        Self {
+        Ok(Self {
             pdfium,
+            attachment_dir: attachment_dir(&document_bytes),
             document_bytes,
             path: pdf_path,
             num_pages,
             render_config,
-        }
+            media_annotations: OnceLock::new(),
+        })
     }
 
     fn document(&self) -> PdfDocument<'_> {
@@ -76,11 +294,264 @@ impl PdfRenderer {
         Some(image)
     }
 
+    /// Size of the page at the given index in PDF points.
+    pub fn page_size(&self, page_idx: usize) -> Option<(f32, f32)> {
+        let document = self.document();
+        let page = document.pages().get(page_idx as u16).ok()?;
+        Some((page.width().value, page.height().value))
+    }
+
+    /// Scans all pages for link, screen and movie annotations referencing media files.
+    ///
+    /// Links need an URI such as `file:clip.mp4`, screen and movie annotations the file name as
+    /// their contents or name. Relative paths are resolved w.r.t. the directory of the PDF.
+    ///
+    /// The document is only scanned once.
+    pub fn media_annotations(&self) -> Vec<MediaAnnotation> {
+        self.media_annotations
+            .get_or_init(|| self.scan_media_annotations())
+            .clone()
+    }
+
+    fn scan_media_annotations(&self) -> Vec<MediaAnnotation> {
+        let document = self.document();
+        let base_dir = self.path.parent().unwrap_or(Path::new(""));
+        // e.g. the `/Movie /F` of LaTeX's `\movie`, which pdfium cannot read
+        let file_specs = media_file_specs(&self.document_bytes);
+        let mut media_annotations = Vec::new();
+        for (page_idx, page) in document.pages().iter().enumerate() {
+            let page_size = (page.width().value, page.height().value);
+            for annotation in page.annotations().iter() {
+                let Ok(bounds) = annotation.bounds() else {
+                    continue;
+                };
+                let file_spec = || {
+                    let rect = [
+                        bounds.left.value,
+                        bounds.bottom.value,
+                        bounds.right.value,
+                        bounds.top.value,
+                    ];
+                    file_specs
+                        .get(page_idx)?
+                        .iter()
+                        .find(|file_spec| file_spec.is_at(rect))
+                        .map(|file_spec| file_spec.file.clone())
+                };
+                let reference = match annotation.annotation_type() {
+                    PdfPageAnnotationType::Link => annotation
+                        .as_link_annotation()
+                        .and_then(|link_annotation| link_annotation.link().ok())
+                        .and_then(|link| link.action())
+                        .and_then(|action| action.as_uri_action().and_then(|uri| uri.uri().ok()))
+                        // launch actions of `\movie[externalviewer]`
+                        .or_else(file_spec),
+                    PdfPageAnnotationType::Screen
+                    | PdfPageAnnotationType::Movie
+                    | PdfPageAnnotationType::RichMedia => annotation
+                        .contents()
+                        .or_else(|| annotation.name())
+                        .filter(|reference| media_path(reference).is_some())
+                        .or_else(file_spec),
+                    _ => None,
+                };
+                let Some(named_path) = reference.as_deref().and_then(media_path) else {
+                    continue;
+                };
+                let is_attachment = named_path
+                    .to_str()
+                    .is_some_and(|path| path.starts_with(ATTACHMENT_PREFIX));
+                let media_path = if is_attachment {
+                    named_path.clone()
+                } else {
+                    base_dir.join(&named_path)
+                };
+                media_annotations.push(MediaAnnotation {
+                    page_idx,
+                    named_path,
+                    media_path,
+                    rect: relative_rect(&bounds, page_size),
+                });
+            }
+        }
+        media_annotations
+    }
+
+    /// Links of the pages by page index, leaving out the ones referencing media files.
+    pub fn links(&self) -> HashMap<usize, Vec<PdfLink>> {
+        let document = self.document();
+        let mut links: HashMap<usize, Vec<PdfLink>> = HashMap::new();
+        for (page_idx, page) in document.pages().iter().enumerate() {
+            let page_size = (page.width().value, page.height().value);
+            for annotation in page.annotations().iter() {
+                let Some(link) = annotation
+                    .as_link_annotation()
+                    .and_then(|link_annotation| link_annotation.link().ok())
+                else {
+                    continue;
+                };
+                let action = link.action();
+                let target = match target_page(link.destination(), action.as_ref()) {
+                    Some(page_idx) => Some(LinkTarget::Page(page_idx)),
+                    None => action
+                        .as_ref()
+                        .and_then(|action| action.as_uri_action()?.uri().ok())
+                        // played on top of the slide instead
+                        .filter(|uri| media_path(uri).is_none())
+                        .map(LinkTarget::Url),
+                };
+                let (Some(target), Ok(bounds)) = (target, annotation.bounds()) else {
+                    continue;
+                };
+                links.entry(page_idx).or_default().push(PdfLink {
+                    rect: relative_rect(&bounds, page_size),
+                    target,
+                });
+            }
+        }
+        links
+    }
+
+    /// Bookmarks of the document in document order.
+    pub fn outline(&self) -> Vec<OutlineEntry> {
+        let document = self.document();
+        let mut entries = Vec::new();
+        // the root is the first bookmark of the top level
+        walk_outline(document.bookmarks().root(), 0, &mut 0, &mut entries);
+        entries
+    }
+
+    /// Slides containing `query` (ignoring case) in the order of the document.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let document = self.document();
+        let mut hits = Vec::new();
+        for (page_idx, page) in document.pages().iter().enumerate() {
+            let Ok(text) = page.text() else {
+                continue;
+            };
+            let Some(snippet) = snippet(&text.all(), query) else {
+                continue;
+            };
+            let page_size = (page.width().value, page.height().value);
+            let mut rects = Vec::new();
+            if let Ok(search) = text.search(query.trim(), &PdfSearchOptions::new()) {
+                for segments in search.iter(PdfSearchDirection::SearchForward) {
+                    rects.extend(
+                        segments
+                            .iter()
+                            .map(|segment| relative_rect(&segment.bounds(), page_size)),
+                    );
+                }
+            }
+            hits.push(SearchHit {
+                page_idx,
+                snippet,
+                rects,
+            });
+        }
+        hits
+    }
+
+    /// Extracts the attachment with the given name to a temporary directory, so that it can be
+    /// opened like any other file. Already extracted attachments are reused.
+    pub fn extract_attachment(&self, name: &str) -> Option<PathBuf> {
+        // attachments are addressed by name only, never write outside of the directory
+        let file_name = Path::new(name).file_name()?;
+        let path = self.attachment_dir.join(file_name);
+        if path.exists() {
+            return Some(path);
+        }
+        let document = self.document();
+        let attachment = document
+            .attachments()
+            .iter()
+            .find(|attachment| attachment.name() == name)?;
+        let bytes = attachment.save_to_bytes().ok()?;
+        fs::create_dir_all(&self.attachment_dir).ok()?;
+        // write to a temporary file first, so that an interrupted extraction is not reused
+        let partial_path = path.with_extension("part");
+        fs::write(&partial_path, bytes).ok()?;
+        fs::rename(&partial_path, &path).ok()?;
+        Some(path)
+    }
+
+    /// Path to open the media of a `video_path` with, extracting attachments if necessary.
+    pub fn resolve_media_path(&self, video_path: &Path) -> PathBuf {
+        match video_path
+            .to_str()
+            .and_then(|path| path.strip_prefix(ATTACHMENT_PREFIX))
+        {
+            Some(name) => self.extract_attachment(name).unwrap_or_else(|| {
+                println!("Could not find attachment `{}` in the PDF", name);
+                video_path.to_path_buf()
+            }),
+            None => video_path.to_path_buf(),
+        }
+    }
+
+    /// Saves a copy of the document with the ink drawn onto its pages.
+    pub fn save_with_ink(&self, ink: &Ink, output_path: &Path) -> Result<(), String> {
+        let document = self.document();
+        for (page_idx, strokes) in &ink.slides {
+            let mut page = document
+                .pages()
+                .get(*page_idx as u16)
+                .map_err(|e| format!("page {}: {}", page_idx, e))?;
+            let (width, height) = (page.width().value, page.height().value);
+            for stroke in strokes {
+                if stroke.tool == InkTool::Eraser {
+                    continue;
+                }
+                // PDF coordinates start at the bottom left
+                let points: Vec<(PdfPoints, PdfPoints)> = stroke
+                    .points
+                    .iter()
+                    .map(|[x, y]| {
+                        (
+                            PdfPoints::new(SizeEntry::Percent(*x).calc_size(width)),
+                            PdfPoints::new(height - SizeEntry::Percent(*y).calc_size(height)),
+                        )
+                    })
+                    .collect();
+                let Some(((x, y), rest)) = points.split_first() else {
+                    continue;
+                };
+                let [r, g, b, a] = stroke.tool.color().to_srgba_unmultiplied();
+                let mut path = PdfPagePathObject::new(
+                    &document,
+                    *x,
+                    *y,
+                    Some(PdfColor::new(r, g, b, a)),
+                    Some(PdfPoints::new(stroke.tool.width() * height)),
+                    None,
+                )
+                .map_err(|e| e.to_string())?;
+                for (x, y) in rest {
+                    path.line_to(*x, *y).map_err(|e| e.to_string())?;
+                }
+                page.objects_mut()
+                    .add_path_object(path)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        document
+            .save_to_file(output_path)
+            .map_err(|e| format!("{}: {}", output_path.to_string_lossy(), e))
+    }
+
     pub fn load_document(&mut self, path: PathBuf) {
-        let (bytes, num_pages) = load_and_calc_pages(&self.pdfium, &path);
+        let (bytes, num_pages) = load_and_calc_pages(&self.pdfium, &path).unwrap_or_else(|e| {
+            panic!(
+                "Could not load pdf document at {}: {}",
+                path.to_string_lossy(),
+                e
+            )
+        });
+        self.attachment_dir = attachment_dir(&bytes);
         self.document_bytes = bytes;
         self.num_pages = num_pages;
         self.path = path.to_owned();
+        self.media_annotations = OnceLock::new();
     }
 
     pub fn set_size(&mut self, size: (i32, i32)) {
@@ -97,4 +568,81 @@ impl PdfRenderer {
             ColorImage::from_rgba_unmultiplied(size, rgba_image.as_flat_samples().as_slice());
         Some(color_image)
     }
+
+    /// Renders the `region` (relative to the page) of the page at the given index, so that it
+    /// fills `size` pixels.
+    pub fn render_region(
+        &self,
+        page_idx: usize,
+        region: egui::Rect,
+        size: [usize; 2],
+    ) -> Option<ColorImage> {
+        println!("Rendering a region of Page {}", page_idx);
+        let page = self.document().pages().get(page_idx as u16).ok()?;
+        let (page_width, page_height) = (page.width().value, page.height().value);
+        // only the region, moved to the origin and magnified to fill the bitmap (pdfium leaves out
+        // form fields when transforming)
+        let render_config = PdfRenderConfig::new()
+            .scale_page_width_by_factor(size[0] as f32 / (region.width() * page_width))
+            .scale_page_height_by_factor(size[1] as f32 / (region.height() * page_height))
+            .set_fixed_size(size[0] as i32, size[1] as i32)
+            .translate(
+                PdfPoints::new(-region.min.x * page_width),
+                PdfPoints::new(-region.min.y * page_height),
+            )
+            .ok()?;
+        let rgba_image = page
+            .render_with_config(&render_config)
+            .ok()?
+            .as_image()
+            .to_rgba8();
+        let size = [rgba_image.width() as usize, rgba_image.height() as usize];
+        Some(ColorImage::from_rgba_unmultiplied(
+            size,
+            rgba_image.as_flat_samples().as_slice(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_media_path() {
+        assert_eq!(media_path("file:clip.mp4"), Some(PathBuf::from("clip.mp4")));
+        assert_eq!(
+            media_path("file:///home/user/clip.MKV"),
+            Some(PathBuf::from("/home/user/clip.MKV"))
+        );
+        assert_eq!(
+            media_path("videos/demo.gif"),
+            Some(PathBuf::from("videos/demo.gif"))
+        );
+        assert_eq!(media_path("https://example.com/clip.mp4"), None);
+        assert_eq!(
+            media_path("file:my%20clip.mp4"),
+            Some(PathBuf::from("my clip.mp4"))
+        );
+        assert_eq!(media_path("file:paper.pdf"), None);
+        assert_eq!(
+            media_path("attachment:demo.mp4"),
+            Some(PathBuf::from("attachment:demo.mp4"))
+        );
+    }
+
+    #[test]
+    fn find_snippet() {
+        assert_eq!(
+            snippet("Results\r\nAblation  Table", "ablation table"),
+            Some("Results Ablation Table".to_owned())
+        );
+        let text = format!("{} needle {}", "a".repeat(40), "b".repeat(40));
+        assert_eq!(
+            snippet(&text, "NEEDLE"),
+            Some(format!("…{} needle {}…", "a".repeat(29), "b".repeat(29)))
+        );
+        assert_eq!(snippet("Results", "table"), None);
+        assert_eq!(snippet("Results", " "), None);
+    }
 }
@@ -0,0 +1,534 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Read,
+};
+
+use flate2::read::ZlibDecoder;
+
+/// Deepest nesting of arrays and dictionaries that is parsed
+const MAX_DEPTH: usize = 32;
+
+/// Value of a PDF object, as far as needed to find the files of media annotations.
+#[derive(Clone, Debug, PartialEq)]
+enum Object {
+    Dict(HashMap<String, Object>),
+    Array(Vec<Object>),
+    Str(Vec<u8>),
+    Name(String),
+    Num(f32),
+    /// Indirect reference by object number
+    Ref(u32),
+    /// Booleans, `null` and anything else not of interest
+    Other,
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || b"()<>[]{}/%".contains(&byte)
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(bytes: &'a [u8], pos: usize) -> Self {
+        Self { bytes, pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if byte == b'%' {
+                while self
+                    .peek()
+                    .is_some_and(|byte| byte != b'\n' && byte != b'\r')
+                {
+                    self.pos += 1;
+                }
+            } else if byte.is_ascii_whitespace() || byte == 0 {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Regular characters up to the next delimiter, e.g. a name or a number.
+    fn token(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self.peek().is_some_and(|byte| !is_delimiter(byte)) {
+            self.pos += 1;
+        }
+        &self.bytes[start..self.pos]
+    }
+
+    fn object(&mut self, depth: usize) -> Option<Object> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        match self.peek()? {
+            b'<' if self.bytes.get(self.pos + 1) == Some(&b'<') => {
+                self.pos += 2;
+                let mut dict = HashMap::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.bytes[self.pos..].starts_with(b">>") {
+                        self.pos += 2;
+                        return Some(Object::Dict(dict));
+                    }
+                    let Object::Name(key) = self.object(depth + 1)? else {
+                        return None;
+                    };
+                    let value = self.object(depth + 1)?;
+                    dict.insert(key, value);
+                }
+            }
+            b'<' => {
+                self.pos += 1;
+                let start = self.pos;
+                while self.peek()? != b'>' {
+                    self.pos += 1;
+                }
+                let hex: Vec<u8> = self.bytes[start..self.pos]
+                    .iter()
+                    .copied()
+                    .filter(u8::is_ascii_hexdigit)
+                    .collect();
+                self.pos += 1;
+                let digit = |byte: u8| (byte as char).to_digit(16).unwrap_or(0) as u8;
+                Some(Object::Str(
+                    hex.chunks(2)
+                        .map(|pair| digit(pair[0]) << 4 | pair.get(1).map_or(0, |b| digit(*b)))
+                        .collect(),
+                ))
+            }
+            b'(' => self.literal_string().map(Object::Str),
+            b'/' => {
+                self.pos += 1;
+                Some(Object::Name(
+                    String::from_utf8_lossy(self.token()).into_owned(),
+                ))
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut array = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek()? == b']' {
+                        self.pos += 1;
+                        return Some(Object::Array(array));
+                    }
+                    array.push(self.object(depth + 1)?);
+                }
+            }
+            byte if byte.is_ascii_digit() || b"+-.".contains(&byte) => {
+                let number = std::str::from_utf8(self.token()).ok()?;
+                // `12 0 R` refers to object 12
+                let before_ref = self.pos;
+                self.skip_whitespace();
+                let generation = self.token();
+                self.skip_whitespace();
+                if !generation.is_empty()
+                    && generation.iter().all(u8::is_ascii_digit)
+                    && self.token() == b"R"
+                {
+                    if let Ok(num) = number.parse() {
+                        return Some(Object::Ref(num));
+                    }
+                }
+                self.pos = before_ref;
+                number.parse().ok().map(Object::Num)
+            }
+            _ => {
+                if self.token().is_empty() {
+                    // stray delimiter such as `)` or `}`
+                    self.pos += 1;
+                }
+                Some(Object::Other)
+            }
+        }
+    }
+
+    /// String in parentheses, which may be nested and contain escapes.
+    fn literal_string(&mut self) -> Option<Vec<u8>> {
+        self.pos += 1;
+        let mut string = Vec::new();
+        let mut nesting = 0;
+        loop {
+            let byte = self.peek()?;
+            self.pos += 1;
+            match byte {
+                b'(' => nesting += 1,
+                b')' if nesting == 0 => return Some(string),
+                b')' => nesting -= 1,
+                b'\\' => {
+                    let escaped = self.peek()?;
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => string.push(b'\n'),
+                        b'r' => string.push(b'\r'),
+                        b't' => string.push(b'\t'),
+                        b'b' => string.push(0x08),
+                        b'f' => string.push(0x0c),
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(digit @ b'0'..=b'7') => {
+                                        value = value * 8 + (digit - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            string.push(value as u8);
+                        }
+                        // line continuation
+                        b'\r' | b'\n' => {
+                            if escaped == b'\r' && self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        _ => string.push(escaped),
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            string.push(byte);
+        }
+    }
+}
+
+/// Text of a PDF string, either UTF-16 with byte order mark or PDFDocEncoding (taken as Latin-1).
+fn decode_text(bytes: &[u8]) -> String {
+    match bytes.strip_prefix(&[0xfe, 0xff]) {
+        Some(utf16) => String::from_utf16_lossy(
+            &utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>(),
+        ),
+        None => bytes.iter().map(|byte| *byte as char).collect(),
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|idx| idx + from)
+}
+
+/// Number of the object defined by `<num> <generation> obj`, given the index of `obj`.
+fn object_number(bytes: &[u8], obj_idx: usize) -> Option<u32> {
+    let skip_back = |end: usize, skipped: fn(&u8) -> bool| {
+        let mut start = end;
+        while start > 0 && skipped(&bytes[start - 1]) {
+            start -= 1;
+        }
+        (start < end).then_some(start)
+    };
+    let generation_start = skip_back(
+        skip_back(obj_idx, u8::is_ascii_whitespace)?,
+        u8::is_ascii_digit,
+    )?;
+    let num_end = skip_back(generation_start, u8::is_ascii_whitespace)?;
+    let num_start = skip_back(num_end, u8::is_ascii_digit)?;
+    std::str::from_utf8(&bytes[num_start..num_end])
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// The objects of a PDF by their number, including the ones in compressed object streams.
+///
+/// Objects are found by scanning for `obj` instead of reading the cross-reference table, which also
+/// works for slightly broken files. Later definitions (incremental updates) replace earlier ones.
+struct Objects(HashMap<u32, Object>);
+
+impl Objects {
+    fn parse(bytes: &[u8]) -> Self {
+        let mut objects = HashMap::new();
+        let mut streams = Vec::new();
+        let mut from = 0;
+        while let Some(idx) = find(bytes, b"obj", from) {
+            from = idx + 3;
+            if bytes.get(from).is_some_and(|byte| !is_delimiter(*byte)) {
+                continue;
+            }
+            let Some(num) = object_number(bytes, idx) else {
+                continue;
+            };
+            let mut parser = Parser::new(bytes, from);
+            let Some(object) = parser.object(0) else {
+                continue;
+            };
+            parser.skip_whitespace();
+            if bytes[parser.pos..].starts_with(b"stream") {
+                let mut data_start = parser.pos + b"stream".len();
+                if bytes.get(data_start) == Some(&b'\r') {
+                    data_start += 1;
+                }
+                if bytes.get(data_start) == Some(&b'\n') {
+                    data_start += 1;
+                }
+                if let Some(data_end) = find(bytes, b"endstream", data_start) {
+                    streams.push((object.clone(), &bytes[data_start..data_end]));
+                    from = data_end;
+                }
+            }
+            objects.insert(num, object);
+        }
+
+        for (dict, data) in streams {
+            if dict.get("Type") != Some(&Object::Name("ObjStm".to_owned())) {
+                continue;
+            }
+            let (Some(Object::Num(count)), Some(Object::Num(first))) =
+                (dict.get("N"), dict.get("First"))
+            else {
+                continue;
+            };
+            let data = match dict.get("Filter") {
+                None => data.to_vec(),
+                Some(Object::Name(filter)) if filter == "FlateDecode" => {
+                    let mut inflated = Vec::new();
+                    // the data may be followed by an end of line
+                    let _ = ZlibDecoder::new(data).read_to_end(&mut inflated);
+                    inflated
+                }
+                _ => continue,
+            };
+            let first = *first as usize;
+            let header = String::from_utf8_lossy(&data[..first.min(data.len())]).into_owned();
+            let numbers: Vec<usize> = header
+                .split_ascii_whitespace()
+                .filter_map(|number| number.parse().ok())
+                .collect();
+            for pair in numbers.chunks_exact(2).take(*count as usize) {
+                let mut parser = Parser::new(&data, first + pair[1]);
+                if let Some(object) = parser.object(0) {
+                    objects.insert(pair[0] as u32, object);
+                }
+            }
+        }
+        Self(objects)
+    }
+
+    /// Follows indirect references.
+    fn resolve<'a>(&'a self, mut object: &'a Object) -> &'a Object {
+        for _ in 0..MAX_DEPTH {
+            match object {
+                Object::Ref(num) => match self.0.get(num) {
+                    Some(target) => object = target,
+                    None => return &Object::Other,
+                },
+                _ => return object,
+            }
+        }
+        &Object::Other
+    }
+
+    /// Value of a dictionary entry, following indirect references.
+    fn get<'a>(&'a self, dict: &'a Object, key: &str) -> Option<&'a Object> {
+        match self.resolve(dict) {
+            Object::Dict(dict) => dict.get(key).map(|value| self.resolve(value)),
+            _ => None,
+        }
+    }
+
+    /// Dictionaries of the pages in the order of the document.
+    fn pages(&self) -> Vec<&Object> {
+        let catalog = self
+            .0
+            .iter()
+            .filter(|(_, object)| object.get("Type") == Some(&Object::Name("Catalog".to_owned())))
+            // the catalog of the last incremental update
+            .max_by_key(|(num, _)| **num)
+            .map(|(_, catalog)| catalog);
+        let mut pages = Vec::new();
+        let mut visited = HashSet::new();
+        if let Some(root) = catalog.and_then(|catalog| catalog.get("Pages")) {
+            self.collect_pages(root, &mut visited, &mut pages);
+        }
+        pages
+    }
+
+    fn collect_pages<'a>(
+        &'a self,
+        node: &'a Object,
+        visited: &mut HashSet<u32>,
+        pages: &mut Vec<&'a Object>,
+    ) {
+        if let Object::Ref(num) = node {
+            if !visited.insert(*num) {
+                return;
+            }
+        }
+        match self.get(node, "Kids") {
+            Some(Object::Array(kids)) => {
+                for kid in kids {
+                    self.collect_pages(kid, visited, pages);
+                }
+            }
+            _ => pages.push(self.resolve(node)),
+        }
+    }
+
+    /// Path of a file specification, either a string or a dictionary.
+    fn file_spec(&self, file_spec: &Object) -> Option<String> {
+        match self.resolve(file_spec) {
+            Object::Str(path) => Some(decode_text(path)),
+            dict @ Object::Dict(_) => ["UF", "F", "Unix", "DOS", "Mac"].iter().find_map(|key| {
+                match self.get(dict, key)? {
+                    Object::Str(path) => Some(decode_text(path)),
+                    _ => None,
+                }
+            }),
+            _ => None,
+        }
+    }
+
+    /// File of a media annotation that pdfium cannot tell.
+    fn annotation_file(&self, annotation: &Object) -> Option<String> {
+        let Some(Object::Name(subtype)) = self.get(annotation, "Subtype") else {
+            return None;
+        };
+        match subtype.as_str() {
+            // `\movie` of the multimedia package
+            "Movie" => self.file_spec(self.get(self.get(annotation, "Movie")?, "F")?),
+            // `\movie[externalviewer]` launches the file
+            "Link" | "Screen" => {
+                let action = self.get(annotation, "A")?;
+                match self.get(action, "S")? {
+                    Object::Name(kind) if kind == "Launch" => self
+                        .get(action, "F")
+                        .or_else(|| self.get(self.get(action, "Win")?, "F"))
+                        .and_then(|file_spec| self.file_spec(file_spec)),
+                    Object::Name(kind) if kind == "Rendition" => {
+                        let clip = self.get(self.get(action, "R")?, "C")?;
+                        self.file_spec(self.get(clip, "D")?)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Object {
+    fn get(&self, key: &str) -> Option<&Object> {
+        match self {
+            Object::Dict(dict) => dict.get(key),
+            _ => None,
+        }
+    }
+}
+
+/// File of a media annotation with the annotation's rect in PDF coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaFileSpec {
+    /// Left, bottom, right and top edge
+    pub rect: [f32; 4],
+    pub file: String,
+}
+
+impl MediaFileSpec {
+    /// Whether this is the annotation at the given rect, up to rounding.
+    pub fn is_at(&self, rect: [f32; 4]) -> bool {
+        self.rect.iter().zip(rect).all(|(a, b)| (a - b).abs() < 0.5)
+    }
+}
+
+/// Files of the media annotations of every page that pdfium does not expose, such as the
+/// `/Movie /F` of a movie annotation or the file of a launch action.
+pub fn media_file_specs(document_bytes: &[u8]) -> Vec<Vec<MediaFileSpec>> {
+    let objects = Objects::parse(document_bytes);
+    objects
+        .pages()
+        .into_iter()
+        .map(|page| {
+            let Some(Object::Array(annotations)) = objects.get(page, "Annots") else {
+                return Vec::new();
+            };
+            annotations
+                .iter()
+                .filter_map(|annotation| {
+                    let file = objects.annotation_file(annotation)?;
+                    let Some(Object::Array(rect)) = objects.get(annotation, "Rect") else {
+                        return None;
+                    };
+                    let rect: Vec<f32> = rect
+                        .iter()
+                        .filter_map(|value| match objects.resolve(value) {
+                            Object::Num(value) => Some(*value),
+                            _ => None,
+                        })
+                        .collect();
+                    let [left, bottom, right, top] = rect[..] else {
+                        return None;
+                    };
+                    Some(MediaFileSpec {
+                        rect: [
+                            left.min(right),
+                            bottom.min(top),
+                            left.max(right),
+                            bottom.max(top),
+                        ],
+                        file,
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_objects() {
+        let object = Parser::new(br"<</A [1 0 R -2.5 (a\(b\)\101) <48 69>] /B /Name>>", 0)
+            .object(0)
+            .unwrap();
+        assert_eq!(
+            object.get("A"),
+            Some(&Object::Array(vec![
+                Object::Ref(1),
+                Object::Num(-2.5),
+                Object::Str(b"a(b)A".to_vec()),
+                Object::Str(b"Hi".to_vec()),
+            ]))
+        );
+        assert_eq!(object.get("B"), Some(&Object::Name("Name".to_owned())));
+    }
+
+    #[test]
+    fn movie_fixture() {
+        // `\movie` as written by pdfLaTeX with the multimedia package, the movie annotation in a
+        // compressed object stream, and `\movie[externalviewer]` on the second page
+        let specs = media_file_specs(include_bytes!("../tests/fixtures/movie.pdf"));
+        assert_eq!(
+            specs,
+            vec![
+                vec![MediaFileSpec {
+                    rect: [72.0, 100.0, 392.0, 280.0],
+                    file: "videos/clip.mp4".to_owned()
+                }],
+                vec![MediaFileSpec {
+                    rect: [72.0, 100.0, 232.0, 190.0],
+                    file: "my clip.mp4".to_owned()
+                }],
+            ]
+        );
+    }
+}
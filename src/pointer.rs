@@ -0,0 +1,87 @@
+use egui::{
+    epaint::{Mesh, Vertex},
+    Color32, Pos2, Rect, Shape, Stroke, TextureHandle,
+};
+
+/// Radius of the spotlight and the zoom lens, relative to the height of the window
+const RADIUS: f32 = 0.15;
+/// Magnification of the zoom lens
+const ZOOM: f32 = 2.5;
+
+/// Helpers for pointing at parts of the slide, drawn on top of slide and videos.
+#[derive(Default)]
+pub struct PointerTools {
+    /// Red dot instead of the cursor
+    pub laser: bool,
+    /// Dims everything except a circle around the cursor
+    pub spotlight: bool,
+    /// Magnifies the slide around the cursor
+    pub lens: bool,
+}
+
+impl PointerTools {
+    pub fn is_active(&self) -> bool {
+        self.laser || self.spotlight || self.lens
+    }
+
+    /// Paints the active tools around the cursor, `slide_rect` is where `slide` is shown.
+    pub fn paint(&self, ctx: &egui::Context, slide: &TextureHandle, slide_rect: Rect) {
+        if !self.is_active() {
+            return;
+        }
+        let Some(pos) = ctx.input(|i| i.pointer.hover_pos()) else {
+            return;
+        };
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("pointer_tools"),
+        ));
+        let screen_rect = ctx.screen_rect();
+        let radius = RADIUS * screen_rect.height();
+
+        if self.spotlight {
+            // a ring wide enough to cover the whole window around the circle
+            let width = 2.0 * screen_rect.size().length();
+            painter.circle_stroke(
+                pos,
+                radius + width / 2.0,
+                Stroke::new(width, Color32::from_black_alpha(180)),
+            );
+        }
+        if self.lens {
+            painter.add(lens(pos, radius, slide, slide_rect));
+            painter.circle_stroke(pos, radius, Stroke::new(2.0, Color32::from_gray(60)));
+        }
+        if self.laser {
+            ctx.set_cursor_icon(egui::CursorIcon::None);
+            painter.circle_filled(pos, 12.0, Color32::from_rgba_unmultiplied(255, 0, 0, 60));
+            painter.circle_filled(pos, 6.0, Color32::from_rgb(255, 30, 30));
+        }
+    }
+}
+
+/// Circle around `center` showing the slide magnified.
+fn lens(center: Pos2, radius: f32, slide: &TextureHandle, slide_rect: Rect) -> Shape {
+    const SEGMENTS: usize = 64;
+    let uv_at = |pos: Pos2| {
+        // the point of the slide shown at `pos`
+        let slide_pos = center + (pos - center) / ZOOM;
+        ((slide_pos - slide_rect.min) / slide_rect.size()).to_pos2()
+    };
+    let vertex = |pos: Pos2| Vertex {
+        pos,
+        uv: uv_at(pos),
+        color: Color32::WHITE,
+    };
+    let mut mesh = Mesh::with_texture(slide.id());
+    mesh.vertices.push(vertex(center));
+    for segment in 0..=SEGMENTS {
+        let angle = segment as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+        mesh.vertices
+            .push(vertex(center + radius * egui::Vec2::angled(angle)));
+        if segment > 0 {
+            mesh.add_triangle(0, segment as u32, segment as u32 + 1);
+        }
+    }
+    Shape::mesh(mesh)
+}
@@ -0,0 +1,125 @@
+use egui::{Color32, Rect};
+
+use crate::{pdf::SearchHit, slides::SlidesCache};
+
+/// Search bar finding the slides containing a phrase.
+#[derive(Default)]
+pub struct Search {
+    open: bool,
+    query: String,
+    /// Query the hits have been found for
+    searched: Option<String>,
+    hits: Vec<SearchHit>,
+    /// Hit jumped to last
+    selected: Option<usize>,
+    /// Whether the search bar should take the keyboard focus
+    focus: bool,
+}
+
+impl Search {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.focus = true;
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.searched = None;
+        self.hits.clear();
+        self.selected = None;
+    }
+
+    /// Shows the search bar with the hits if open, returns the slide of a selected hit.
+    ///
+    /// `Enter` searches, and jumps to the next hit when pressed again; `Esc` closes it.
+    pub fn ui(&mut self, ctx: &egui::Context, slides: &SlidesCache) -> Option<usize> {
+        if !self.open {
+            return None;
+        }
+        let mut jump_to = None;
+        egui::Window::new("Search")
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 10.0])
+            .title_bar(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Search the slides")
+                        .desired_width(400.0),
+                );
+                // only after the text edit has been shown, so that it does not get the `/` typed
+                // to open the search bar
+                if self.focus {
+                    response.request_focus();
+                    self.focus = false;
+                }
+                let (enter, escape) = ui.input(|i| {
+                    (
+                        i.key_pressed(egui::Key::Enter),
+                        i.key_pressed(egui::Key::Escape),
+                    )
+                });
+                if escape {
+                    self.close();
+                    return;
+                }
+                if response.lost_focus() && enter {
+                    if self.searched.as_ref() == Some(&self.query) {
+                        if !self.hits.is_empty() {
+                            let next = self.selected.map_or(0, |idx| (idx + 1) % self.hits.len());
+                            self.selected = Some(next);
+                            jump_to = Some(self.hits[next].page_idx);
+                        }
+                    } else {
+                        self.hits = slides.search(&self.query);
+                        self.searched = Some(self.query.clone());
+                        self.selected = None;
+                    }
+                    response.request_focus();
+                }
+
+                match &self.searched {
+                    Some(query) if self.hits.is_empty() => {
+                        ui.label(format!("No slide contains \"{}\"", query));
+                    }
+                    _ => {}
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for (idx, hit) in self.hits.iter().enumerate() {
+                            let text = format!("{}  {}", hit.page_idx, hit.snippet);
+                            if ui
+                                .selectable_label(self.selected == Some(idx), text)
+                                .clicked()
+                            {
+                                self.selected = Some(idx);
+                                jump_to = Some(hit.page_idx);
+                            }
+                        }
+                    });
+            });
+        jump_to
+    }
+
+    /// Highlights the hits on the slide shown at `slide_rect`.
+    pub fn paint_hits(&self, ui: &egui::Ui, page_idx: usize, slide_rect: Rect) {
+        let painter = ui.painter_at(slide_rect);
+        let rects = self
+            .hits
+            .iter()
+            .filter(|hit| hit.page_idx == page_idx)
+            .flat_map(|hit| &hit.rects);
+        for rect in rects {
+            let rect = Rect::from_min_max(
+                slide_rect.lerp_inside(rect.min.to_vec2()),
+                slide_rect.lerp_inside(rect.max.to_vec2()),
+            );
+            painter.rect_filled(
+                rect.expand(2.0),
+                2.0,
+                Color32::from_rgba_unmultiplied(255, 220, 0, 90),
+            );
+        }
+    }
+}
@@ -1,10 +1,19 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use egui::ColorImage;
 
-use crate::pdf::PdfRenderer;
-use crate::video::VideoPlayer;
-use crate::VideoEntry;
+use crate::keyframes::AnimatedState;
+use crate::overlay::{create_overlay, SlideOverlay};
+use crate::pdf::{OutlineEntry, PdfLink, PdfRenderer, SearchHit};
+use crate::{Easing, Fit, PosRequest, SizeRequest, VideoEntry};
+
+/// Seconds to blow an overlay up to the whole slide or back
+const FULLSCREEN_DURATION: f32 = 0.3;
 
 #[derive(Default)]
 struct ImageState {
@@ -22,11 +31,149 @@ pub struct SlidesCache {
     rendered_slides: HashMap<usize, (ColorImage, ImageState)>,
 
     video_entries: Vec<SlidesVideoEntry>,
+    /// Videos referenced by annotations of the PDF
+    pdf_video_entries: Vec<VideoEntry>,
 }
 
 struct SlidesVideoEntry {
     entry: VideoEntry,
-    player: VideoPlayer,
+    /// Path to open the video with (e.g. of an extracted attachment)
+    media_path: PathBuf,
+    /// `None` for kinds of overlays which have not been registered
+    player: Option<Box<dyn SlideOverlay>>,
+    /// Where the video has been rendered to during the last frame
+    rect: Option<egui::Rect>,
+    /// Whether the entry stems from the PDF instead of the configuration
+    from_pdf: bool,
+    /// When the animation of the entry has been started on the current slide
+    animation_start: Option<Instant>,
+    /// Set while the overlay is blown up to the whole slide, or on its way there or back
+    fullscreen: Option<Fullscreen>,
+}
+
+struct Fullscreen {
+    /// Whether on the way to the whole slide instead of back
+    expanded: bool,
+    since: Instant,
+}
+
+impl Fullscreen {
+    /// How far the overlay is on its way to the whole slide, from 0 to 1.
+    fn progress(&self) -> f32 {
+        let t = Easing::EaseInOut.apply(self.since.elapsed().as_secs_f32() / FULLSCREEN_DURATION);
+        if self.expanded {
+            t
+        } else {
+            1.0 - t
+        }
+    }
+
+    fn is_animating(&self) -> bool {
+        self.since.elapsed().as_secs_f32() < FULLSCREEN_DURATION
+    }
+
+    /// Starts blowing the overlay up, or turns back where it currently is.
+    fn toggle(fullscreen: &mut Option<Fullscreen>) {
+        let (expanded, elapsed) = match fullscreen {
+            None => (true, 0.0),
+            Some(fullscreen) => (
+                !fullscreen.expanded,
+                (FULLSCREEN_DURATION - fullscreen.since.elapsed().as_secs_f32()).max(0.0),
+            ),
+        };
+        let now = Instant::now();
+        *fullscreen = Some(Fullscreen {
+            expanded,
+            since: now
+                .checked_sub(Duration::from_secs_f32(elapsed))
+                .unwrap_or(now),
+        });
+    }
+}
+
+impl SlidesVideoEntry {
+    fn new(entry: VideoEntry, pdf_renderer: &PdfRenderer) -> Self {
+        let player = create_overlay(&entry);
+        if player.is_none() {
+            println!(
+                "Unknown type {:?} of `{}`, it is not shown",
+                entry.media_type,
+                entry.video_path.to_string_lossy()
+            );
+        }
+        Self {
+            media_path: pdf_renderer.resolve_media_path(&entry.video_path),
+            player,
+            entry,
+            rect: None,
+            from_pdf: false,
+            animation_start: None,
+            fullscreen: None,
+        }
+    }
+
+    fn from_pdf(entry: VideoEntry, pdf_renderer: &PdfRenderer) -> Self {
+        Self {
+            from_pdf: true,
+            ..Self::new(entry, pdf_renderer)
+        }
+    }
+}
+
+/// Rect of an overlay of the given natural size placed on the slide.
+fn place(
+    pos: &PosRequest,
+    size: &SizeRequest,
+    video_dim: egui::Vec2,
+    slide_pos: egui::Pos2,
+    slide_size: egui::Vec2,
+) -> egui::Rect {
+    let slide_size = (slide_size.x, slide_size.y);
+    let scaled_pos = pos.by_bbox(slide_size);
+    let scaled_pos = egui::vec2(scaled_pos.0, scaled_pos.1);
+    let scaled_size = size.by_bbox((video_dim.x, video_dim.y), slide_size);
+    let scaled_size = egui::vec2(scaled_size.0, scaled_size.1);
+    egui::Rect {
+        min: slide_pos + scaled_pos,
+        max: slide_pos + scaled_pos + scaled_size,
+    }
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (fs::canonicalize(a), fs::canonicalize(b)),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+/// Appends the videos of the PDF to the configured ones.
+///
+/// Explicit configuration wins: a video of the PDF is dropped if the configuration already
+/// places the same file on that slide.
+fn merge_video_entries(
+    video_entries: Vec<VideoEntry>,
+    pdf_video_entries: &[VideoEntry],
+    pdf_renderer: &PdfRenderer,
+) -> Vec<SlidesVideoEntry> {
+    let pdf_video_entries = pdf_video_entries
+        .iter()
+        .filter(|pdf_entry| {
+            !video_entries.iter().any(|entry| {
+                is_same_file(&entry.video_path, &pdf_entry.video_path)
+                    && pdf_entry
+                        .slide_nums
+                        .iter()
+                        .any(|slide_num| entry.slide_nums.contains(slide_num))
+            })
+        })
+        .map(|pdf_entry| SlidesVideoEntry::from_pdf(pdf_entry.clone(), pdf_renderer))
+        .collect::<Vec<_>>();
+    video_entries
+        .into_iter()
+        .map(|entry| SlidesVideoEntry::new(entry, pdf_renderer))
+        .chain(pdf_video_entries)
+        .collect()
 }
 
 impl SlidesCache {
@@ -35,14 +182,22 @@ impl SlidesCache {
         window_width: i32,
         window_height: i32,
         video_entries: Vec<VideoEntry>, // TODO: Expect Vec<SlidesVideoEntry> directly
+        pdf_media: HashMap<PathBuf, PathBuf>,
     ) -> Self {
-        let video_entries = video_entries
-            .into_iter()
-            .map(|entry| SlidesVideoEntry {
-                entry,
-                player: VideoPlayer::new(),
+        let pdf_video_entries: Vec<VideoEntry> = slides
+            .pdf_renderer
+            .media_annotations()
+            .iter()
+            .map(|media_annotation| {
+                let mut entry = media_annotation.as_video_entry();
+                if let Some(media_path) = pdf_media.get(&media_annotation.named_path) {
+                    entry.video_path = media_path.clone();
+                }
+                entry
             })
             .collect();
+        let video_entries =
+            merge_video_entries(video_entries, &pdf_video_entries, &slides.pdf_renderer);
         Self {
             slides,
             window_width,
@@ -51,6 +206,7 @@ impl SlidesCache {
             needs_redraw: true,
             rendered_slides: HashMap::default(),
             video_entries,
+            pdf_video_entries,
         }
     }
 
@@ -58,6 +214,26 @@ impl SlidesCache {
         self.slides.pdf_renderer.num_pages
     }
 
+    /// Links of the PDF by slide index.
+    pub fn links(&self) -> HashMap<usize, Vec<PdfLink>> {
+        self.slides.pdf_renderer.links()
+    }
+
+    /// Bookmarks of the PDF in document order.
+    pub fn outline(&self) -> Vec<OutlineEntry> {
+        self.slides.pdf_renderer.outline()
+    }
+
+    /// Slides containing `query`.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        self.slides.pdf_renderer.search(query)
+    }
+
+    /// Path to open the media of a `video_path` / `audio_path` with.
+    pub fn resolve_media_path(&self, media_path: &Path) -> PathBuf {
+        self.slides.pdf_renderer.resolve_media_path(media_path)
+    }
+
     pub fn change_size(&mut self, window_width: i32, window_height: i32) {
         // this will outdate the cache, and trigger a re-generation
         if self.window_width != window_width || self.window_height != window_height {
@@ -72,13 +248,53 @@ impl SlidesCache {
     }
 
     pub fn change_video_entries(&mut self, video_entries: Vec<VideoEntry>) {
-        self.video_entries = video_entries
-            .into_iter()
-            .map(|entry| SlidesVideoEntry {
-                entry,
-                player: VideoPlayer::new(),
-            })
-            .collect();
+        self.video_entries = merge_video_entries(
+            video_entries,
+            &self.pdf_video_entries,
+            &self.slides.pdf_renderer,
+        );
+    }
+
+    /// The configured video entries (without the ones of the PDF).
+    pub fn video_entries(&self) -> Vec<VideoEntry> {
+        self.video_entries
+            .iter()
+            .filter(|video_entry| !video_entry.from_pdf)
+            .map(|video_entry| video_entry.entry.clone())
+            .collect()
+    }
+
+    pub fn video_entry_mut(&mut self, idx: usize) -> Option<&mut VideoEntry> {
+        self.video_entries
+            .get_mut(idx)
+            .map(|video_entry| &mut video_entry.entry)
+    }
+
+    pub fn push_video_entry(&mut self, entry: VideoEntry) {
+        let video_entry = SlidesVideoEntry::new(entry, &self.slides.pdf_renderer);
+        self.video_entries.push(video_entry);
+    }
+
+    /// Whether all overlays of the slide have played to their end, `None` if it has none.
+    pub fn overlays_finished(&self, page_idx: usize) -> Option<bool> {
+        self.video_entries
+            .iter()
+            .filter(|video_entry| video_entry.entry.slide_nums.contains(&page_idx))
+            .filter_map(|video_entry| video_entry.player.as_ref())
+            .filter(|player| player.is_playing())
+            .map(|player| player.is_finished())
+            .reduce(|a, b| a && b)
+    }
+
+    /// Rects of the configured videos rendered during the last frame, with the index of their
+    /// entry. Videos of the PDF are placed by the PDF and therefore left out.
+    pub fn video_rects(&self) -> Vec<(usize, egui::Rect)> {
+        self.video_entries
+            .iter()
+            .enumerate()
+            .filter(|(_, video_entry)| !video_entry.from_pdf)
+            .filter_map(|(idx, video_entry)| video_entry.rect.map(|rect| (idx, rect)))
+            .collect()
     }
 
     fn update_img(&mut self) -> Option<ColorImage> {
@@ -117,47 +333,160 @@ impl SlidesCache {
         }
     }
 
+    /// Renders the `region` (relative to the slide) of the slide, so that it fills `size` pixels.
+    pub fn render_region(
+        &self,
+        page_idx: usize,
+        region: egui::Rect,
+        size: [usize; 2],
+    ) -> Option<ColorImage> {
+        self.slides
+            .pdf_renderer
+            .render_region(page_idx, region, size)
+    }
+
     /// Handles the rendering of the videos of this slide to the given context and ui.
     ///
-    /// Needs position and size of the slides to render the videos.
+    /// Needs position and size of the slides to render the videos. Keys and double-clicks (e.g.
+    /// triggers of animations) are only handled if `keys_enabled`.
     pub fn handle_video(
         &mut self,
         page_idx: usize,
         slide_pos: egui::Pos2,
         slide_size: egui::Vec2,
+        keys_enabled: bool,
         ctx: &egui::Context,
         ui: &mut egui::Ui,
     ) {
-        for SlidesVideoEntry { entry, player } in self.video_entries.iter_mut() {
+        // paint in the order of the z-index, equal ones in the order of the configuration
+        let mut video_entries: Vec<&mut SlidesVideoEntry> = self.video_entries.iter_mut().collect();
+        // with the ones blown up to the whole slide on top
+        video_entries.sort_by_key(|video_entry| {
+            (
+                video_entry.fullscreen.is_some(),
+                video_entry.entry.appearance.z_index,
+            )
+        });
+
+        // double-click or `M` blows up the topmost overlay under the pointer or shrinks it back
+        let toggle_pos = ctx.input(|i| {
+            (keys_enabled
+                && (i
+                    .pointer
+                    .button_double_clicked(egui::PointerButton::Primary)
+                    || i.key_pressed(egui::Key::M)))
+            .then(|| i.pointer.hover_pos())
+            .flatten()
+        });
+        if let Some(toggle_pos) = toggle_pos {
+            if let Some(video_entry) = video_entries.iter_mut().rev().find(|video_entry| {
+                video_entry
+                    .rect
+                    .is_some_and(|rect| rect.contains(toggle_pos))
+            }) {
+                Fullscreen::toggle(&mut video_entry.fullscreen);
+            }
+        }
+
+        for SlidesVideoEntry {
+            entry,
+            media_path,
+            player,
+            rect: rendered_rect,
+            animation_start,
+            fullscreen,
+            ..
+        } in video_entries
+        {
+            *rendered_rect = None;
+            let Some(player) = player else {
+                continue;
+            };
             // video should not be rendered for this slide
             if !entry.slide_nums.contains(&page_idx) {
                 if player.is_playing() {
                     // TODO: config it in a way where I could also choose not to destroy it
                     player.destroy();
                 }
+                *animation_start = None;
+                *fullscreen = None;
                 // => destroy / pause / ...
                 continue;
             }
 
             if !player.is_playing() {
-                player.init(ctx, entry.video_path.to_str().unwrap());
+                player.init(ctx, media_path.to_str().unwrap());
                 player.start();
             }
 
-            // render video
-            let slide_size = (slide_size.x, slide_size.y);
-            let scaled_pos = entry.pos.by_bbox(slide_size);
-            let scaled_pos = egui::vec2(scaled_pos.0, scaled_pos.1);
-            let video_dim = player.size().unwrap();
-            let video_dim = (video_dim.x, video_dim.y);
-            let scaled_size = entry.size.by_bbox(video_dim, slide_size);
-            let scaled_size = egui::vec2(scaled_size.0, scaled_size.1);
-            let rect = egui::Rect {
-                min: slide_pos + scaled_pos,
-                max: slide_pos + scaled_pos + scaled_size,
+            // e.g. an image that could not be decoded
+            let Some(video_dim) = player.size() else {
+                continue;
             };
+            // only the cropped part counts
+            let uv = entry.appearance.crop.uv();
+            let video_dim = video_dim * uv.size();
+            let mut state = AnimatedState {
+                rect: place(&entry.pos, &entry.size, video_dim, slide_pos, slide_size),
+                opacity: 1.0,
+            };
+            if let Some(animation) = &entry.animation {
+                match animation.trigger {
+                    None if animation_start.is_none() => *animation_start = Some(Instant::now()),
+                    Some(key) if keys_enabled && ctx.input(|i| i.key_pressed(key)) => {
+                        *animation_start = Some(Instant::now())
+                    }
+                    _ => {}
+                }
+                // waiting for the trigger at the start
+                let time = animation_start
+                    .map(|start| start.elapsed().as_secs_f32())
+                    .unwrap_or_default();
+                if let Some(animated_state) = animation.state_at(time, |keyframe| AnimatedState {
+                    rect: place(
+                        keyframe.pos.as_ref().unwrap_or(&entry.pos),
+                        keyframe.size.as_ref().unwrap_or(&entry.size),
+                        video_dim,
+                        slide_pos,
+                        slide_size,
+                    ),
+                    opacity: keyframe.opacity.unwrap_or(1.0),
+                }) {
+                    state = animated_state;
+                }
+                if animation_start.is_some() && time < animation.duration() {
+                    ctx.request_repaint();
+                }
+            }
+            let mut rect = state.rect;
+            let (mut paint_rect, uv) = match entry.size {
+                SizeRequest::Size(..) => entry.appearance.fit.apply(rect, video_dim, uv),
+                SizeRequest::AutoWidth(_) | SizeRequest::AutoHeight(_) => (rect, uv),
+            };
+            if let Some(fullscreen_state) = fullscreen {
+                // as large as possible, showing the same part of the video
+                let (fullscreen_rect, _) = Fit::Contain.apply(
+                    egui::Rect::from_min_size(slide_pos, slide_size),
+                    paint_rect.size(),
+                    uv,
+                );
+                let t = fullscreen_state.progress();
+                rect = rect.lerp_towards(&fullscreen_rect, t);
+                paint_rect = paint_rect.lerp_towards(&fullscreen_rect, t);
+                if fullscreen_state.is_animating() {
+                    ctx.request_repaint();
+                } else if !fullscreen_state.expanded {
+                    *fullscreen = None;
+                }
+            }
             // render to ui
-            player.render(ui, rect);
+            let opacity = ui.opacity();
+            ui.multiply_opacity(state.opacity);
+            entry
+                .appearance
+                .render(ui, paint_rect, uv, |ui, rect| player.render(ui, rect));
+            ui.set_opacity(opacity);
+            *rendered_rect = Some(rect);
         }
     }
 }
@@ -0,0 +1,240 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use egui::{Color32, RichText};
+use serde::Serialize;
+
+/// Target duration of a talk and time budgets of its slides.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TimerSettings {
+    pub duration: Option<Duration>,
+    /// Budgets of single slides by their index, the others share the rest of `duration`
+    pub slide_budgets: HashMap<usize, Duration>,
+}
+
+impl TimerSettings {
+    /// Time budget of the slide, if any.
+    pub fn budget(&self, page_idx: usize, num_pages: usize) -> Option<Duration> {
+        if let Some(budget) = self.slide_budgets.get(&page_idx) {
+            return Some(*budget);
+        }
+        let duration = self.duration?;
+        let budgets: Vec<Duration> = self
+            .slide_budgets
+            .iter()
+            .filter(|(page_idx, _)| **page_idx < num_pages)
+            .map(|(_, budget)| *budget)
+            .collect();
+        let budgeted: Duration = budgets.iter().sum();
+        let remaining_pages = num_pages.saturating_sub(budgets.len()).max(1);
+        Some(duration.saturating_sub(budgeted) / remaining_pages as u32)
+    }
+}
+
+/// `m:ss`, e.g. `12:05`.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[derive(Serialize)]
+struct RehearsedSlide {
+    slide: usize,
+    seconds: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    budget: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct Rehearsal {
+    seconds: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<f32>,
+    slides: Vec<RehearsedSlide>,
+}
+
+/// Measures the talk and the time spent on each slide.
+pub struct TalkTimer {
+    pub settings: TimerSettings,
+    /// Whether it is shown on top of the slides
+    pub visible: bool,
+    started: Instant,
+    page_idx: usize,
+    slide_started: Instant,
+    /// Time spent on the slides before the current visit
+    spent: BTreeMap<usize, Duration>,
+}
+
+impl TalkTimer {
+    pub fn new(settings: TimerSettings) -> Self {
+        Self {
+            settings,
+            visible: false,
+            started: Instant::now(),
+            page_idx: 0,
+            slide_started: Instant::now(),
+            spent: BTreeMap::new(),
+        }
+    }
+
+    /// Starts over, e.g. right before the talk.
+    pub fn restart(&mut self) {
+        self.started = Instant::now();
+        self.slide_started = Instant::now();
+        self.spent.clear();
+    }
+
+    pub fn change_slide(&mut self, page_idx: usize) {
+        *self.spent.entry(self.page_idx).or_default() += self.slide_started.elapsed();
+        self.page_idx = page_idx;
+        self.slide_started = Instant::now();
+    }
+
+    /// Time spent on each slide so far, including the current visit.
+    fn spent_per_slide(&self) -> BTreeMap<usize, Duration> {
+        let mut spent = self.spent.clone();
+        *spent.entry(self.page_idx).or_default() += self.slide_started.elapsed();
+        spent
+    }
+
+    /// Time spent on the current slide, over all visits.
+    fn slide_time(&self) -> Duration {
+        self.spent.get(&self.page_idx).copied().unwrap_or_default() + self.slide_started.elapsed()
+    }
+
+    /// How far behind the schedule the talk is, negative when ahead of it.
+    fn behind_schedule(&self, num_pages: usize) -> Option<f32> {
+        let budget = self.settings.budget(self.page_idx, num_pages)?;
+        let planned_start: Duration = (0..self.page_idx)
+            .filter_map(|page_idx| self.settings.budget(page_idx, num_pages))
+            .sum();
+        let planned = planned_start + self.slide_time().min(budget);
+        Some(self.started.elapsed().as_secs_f32() - planned.as_secs_f32())
+    }
+
+    /// Shows the timer in the top right corner, or only a warning if the slide overruns.
+    pub fn ui(&self, ctx: &egui::Context, num_pages: usize) {
+        let budget = self.settings.budget(self.page_idx, num_pages);
+        let slide_time = self.slide_time();
+        let overrun = budget.is_some_and(|budget| slide_time > budget);
+        if !self.visible && !overrun {
+            // only to notice overruns
+            if budget.is_some() {
+                ctx.request_repaint_after(Duration::from_secs(1));
+            }
+            return;
+        }
+        ctx.request_repaint_after(Duration::from_secs(1));
+        let warning = |text: String, warn: bool| {
+            let text = RichText::new(text).monospace();
+            if warn {
+                text.color(Color32::from_rgb(230, 60, 50))
+            } else {
+                text
+            }
+        };
+        egui::Area::new(egui::Id::new("talk_timer"))
+            .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let slide_text = match budget {
+                        Some(budget) => format!(
+                            "slide {} / {}",
+                            format_duration(slide_time),
+                            format_duration(budget)
+                        ),
+                        None => format!("slide {}", format_duration(slide_time)),
+                    };
+                    if !self.visible {
+                        ui.label(warning(slide_text, overrun));
+                        return;
+                    }
+                    let elapsed = self.started.elapsed();
+                    let talk_text = match self.settings.duration {
+                        Some(duration) => format!(
+                            "talk  {} / {}",
+                            format_duration(elapsed),
+                            format_duration(duration)
+                        ),
+                        None => format!("talk  {}", format_duration(elapsed)),
+                    };
+                    ui.label(warning(
+                        talk_text,
+                        self.settings
+                            .duration
+                            .is_some_and(|duration| elapsed > duration),
+                    ));
+                    ui.label(warning(slide_text, overrun));
+                    if let Some(behind) = self.behind_schedule(num_pages) {
+                        let text = format!(
+                            "{} {}",
+                            if behind > 0.0 { "behind" } else { "ahead " },
+                            format_duration(Duration::from_secs_f32(behind.abs()))
+                        );
+                        ui.label(warning(text, behind > 0.0));
+                    }
+                });
+            });
+    }
+
+    /// Writes the time spent on each slide to a `.csv` or (otherwise) `.json` file.
+    pub fn write_rehearsal(&self, path: &Path, num_pages: usize) -> Result<(), String> {
+        let seconds = |duration: Duration| duration.as_secs_f32();
+        let rehearsal = Rehearsal {
+            seconds: seconds(self.started.elapsed()),
+            target: self.settings.duration.map(seconds),
+            slides: self
+                .spent_per_slide()
+                .into_iter()
+                .map(|(page_idx, spent)| RehearsedSlide {
+                    slide: page_idx,
+                    seconds: seconds(spent),
+                    budget: self.settings.budget(page_idx, num_pages).map(seconds),
+                })
+                .collect(),
+        };
+        let is_csv = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+        let content = if is_csv {
+            let mut csv = "slide,seconds,budget\n".to_owned();
+            for slide in &rehearsal.slides {
+                csv += &format!(
+                    "{},{:.1},{}\n",
+                    slide.slide,
+                    slide.seconds,
+                    slide
+                        .budget
+                        .map(|budget| format!("{:.1}", budget))
+                        .unwrap_or_default()
+                );
+            }
+            csv
+        } else {
+            serde_json::to_string_pretty(&rehearsal).map_err(|e| e.to_string())?
+        };
+        fs::write(path, content).map_err(|e| format!("{}: {}", path.to_string_lossy(), e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn share_duration_between_slides() {
+        let settings = TimerSettings {
+            duration: Some(Duration::from_secs(600)),
+            slide_budgets: HashMap::from([(0, Duration::from_secs(120))]),
+        };
+        assert_eq!(settings.budget(0, 5), Some(Duration::from_secs(120)));
+        assert_eq!(settings.budget(3, 5), Some(Duration::from_secs(120)));
+        assert_eq!(TimerSettings::default().budget(3, 5), None);
+        assert_eq!(format_duration(Duration::from_secs(725)), "12:05");
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use egui::{epaint::Mesh, Color32, Pos2, Rect, TextureHandle, Vec2};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::Easing;
+
+const FULL_UV: Rect = Rect::from_min_max(Pos2::ZERO, egui::pos2(1.0, 1.0));
+
+/// Number of blocks `[columns, rows]` a slide dissolves into
+const DISSOLVE_BLOCKS: [u32; 2] = [64, 36];
+
+/// How the next slide replaces the previous one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionKind {
+    /// Switch instantly
+    #[default]
+    None,
+    /// Cross-fade from the previous slide
+    Fade,
+    /// The next slide pushes the previous one out, in the direction of navigation
+    Push,
+    /// The next slide appears in small blocks in random order
+    Dissolve,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    pub duration: Duration,
+}
+
+/// Transitions to the slides, configured for all slides and for single ones.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Transitions {
+    pub default: Option<Transition>,
+    /// Override the default for the slide with the index
+    pub slides: HashMap<usize, Transition>,
+}
+
+impl Transitions {
+    /// Transition when entering the slide, `None` to switch instantly.
+    pub fn to_slide(&self, page_idx: usize) -> Option<Transition> {
+        self.slides
+            .get(&page_idx)
+            .or(self.default.as_ref())
+            .copied()
+            .filter(|transition| {
+                transition.kind != TransitionKind::None && !transition.duration.is_zero()
+            })
+    }
+}
+
+/// Pseudo-random point in time (from 0 to 1) at which a block appears when dissolving.
+fn block_threshold(x: u32, y: u32) -> f32 {
+    let mut hash = x.wrapping_mul(0x9e37_79b9) ^ y.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^= hash >> 12;
+    (hash % 1024) as f32 / 1024.0
+}
+
+/// Transition from the previous slide in progress.
+pub struct RunningTransition {
+    transition: Transition,
+    since: Instant,
+    /// Whether navigating to a later slide
+    forward: bool,
+}
+
+impl RunningTransition {
+    pub fn new(transition: Transition, forward: bool) -> Self {
+        Self {
+            transition,
+            since: Instant::now(),
+            forward,
+        }
+    }
+
+    fn progress(&self) -> f32 {
+        (self.since.elapsed().as_secs_f32() / self.transition.duration.as_secs_f32()).min(1.0)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    /// Paints the previous slide and the next one (to `rect`) at the current progress.
+    ///
+    /// Returns the offset and opacity to render the overlays of the next slide with.
+    pub fn paint(
+        &self,
+        painter: &egui::Painter,
+        previous: &TextureHandle,
+        next: &TextureHandle,
+        rect: Rect,
+    ) -> (Vec2, f32) {
+        let t = self.progress();
+        let previous_rect = Rect::from_center_size(rect.center(), previous.size_vec2());
+        match self.transition.kind {
+            TransitionKind::None => {
+                painter.image(next.id(), rect, FULL_UV, Color32::WHITE);
+                (Vec2::ZERO, 1.0)
+            }
+            TransitionKind::Fade => {
+                painter.image(previous.id(), previous_rect, FULL_UV, Color32::WHITE);
+                painter.image(next.id(), rect, FULL_UV, Color32::WHITE.gamma_multiply(t));
+                (Vec2::ZERO, t)
+            }
+            TransitionKind::Push => {
+                let t = Easing::EaseInOut.apply(t);
+                // across the whole window, not only the slide
+                let width = painter.clip_rect().width().max(rect.width());
+                let direction = if self.forward { 1.0 } else { -1.0 };
+                let previous_offset = egui::vec2(-direction * width * t, 0.0);
+                let next_offset = egui::vec2(direction * width * (1.0 - t), 0.0);
+                painter.image(
+                    previous.id(),
+                    previous_rect.translate(previous_offset),
+                    FULL_UV,
+                    Color32::WHITE,
+                );
+                painter.image(
+                    next.id(),
+                    rect.translate(next_offset),
+                    FULL_UV,
+                    Color32::WHITE,
+                );
+                (next_offset, 1.0)
+            }
+            TransitionKind::Dissolve => {
+                painter.image(previous.id(), previous_rect, FULL_UV, Color32::WHITE);
+                let [columns, rows] = DISSOLVE_BLOCKS;
+                let block_uv = egui::vec2(1.0 / columns as f32, 1.0 / rows as f32);
+                let mut mesh = Mesh::with_texture(next.id());
+                for y in 0..rows {
+                    for x in 0..columns {
+                        if block_threshold(x, y) >= t {
+                            continue;
+                        }
+                        let uv = Rect::from_min_size(
+                            egui::pos2(x as f32 * block_uv.x, y as f32 * block_uv.y),
+                            block_uv,
+                        );
+                        let block_rect = Rect::from_min_max(
+                            rect.min + uv.min.to_vec2() * rect.size(),
+                            rect.min + uv.max.to_vec2() * rect.size(),
+                        );
+                        mesh.add_rect_with_uv(block_rect, uv, Color32::WHITE);
+                    }
+                }
+                painter.add(mesh);
+                (Vec2::ZERO, t)
+            }
+        }
+    }
+}
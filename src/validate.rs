@@ -0,0 +1,405 @@
+use std::{fmt, path::Path};
+
+use pdfium_render::prelude::PdfRenderConfig;
+
+use crate::{
+    alpha_video::AlphaVideoPlayer,
+    app::VIEWER_KEYS,
+    image_player::ImagePlayer,
+    image_sequence::{is_image_sequence, ImageSequencePlayer},
+    overlay,
+    pdf::PdfRenderer,
+    video::VideoPlayer,
+    Config, MediaType, SizeRequest, VideoEntry,
+};
+
+/// A single problem found in a configuration, including where it was found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    /// Location inside of the configuration, e.g. `entries[2].slide_num`
+    pub location: String,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(location: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Checks the configuration against the given PDF without starting the viewer.
+///
+/// Collects all problems instead of stopping at the first one.
+pub fn validate(pdf_path: &Path, config: Config) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    let pdf_renderer = if pdf_path.exists() {
+        match PdfRenderer::try_new(PdfRenderConfig::new(), pdf_path.to_path_buf()) {
+            Ok(pdf_renderer) => Some(pdf_renderer),
+            Err(e) => {
+                problems.push(Problem::new(
+                    pdf_path.to_string_lossy(),
+                    format!("PDF document could not be loaded: {}", e),
+                ));
+                None
+            }
+        }
+    } else {
+        problems.push(Problem::new(
+            pdf_path.to_string_lossy(),
+            "PDF document does not exist",
+        ));
+        None
+    };
+
+    for (idx, audio) in config.audio_entries().iter().enumerate() {
+        let location = format!("audio[{}]", idx);
+        let media_path = match &pdf_renderer {
+            Some(pdf_renderer) => pdf_renderer.resolve_media_path(&audio.audio_path),
+            None => audio.audio_path.clone(),
+        };
+        if !media_path.exists() {
+            problems.push(Problem::new(
+                format!("{}.audio_path", location),
+                format!("`{}` does not exist", audio.audio_path.to_string_lossy()),
+            ));
+        }
+        if audio.slide_range.is_empty() {
+            problems.push(Problem::new(
+                format!("{}.slide_range", location),
+                "the first slide comes after the last one",
+            ));
+        }
+        if let Some(pdf_renderer) = &pdf_renderer {
+            if *audio.slide_range.end() >= pdf_renderer.num_pages {
+                problems.push(Problem::new(
+                    format!("{}.slide_range", location),
+                    format!(
+                        "slide {} does not exist, the document has {} slides (counting from 0)",
+                        audio.slide_range.end(),
+                        pdf_renderer.num_pages
+                    ),
+                ));
+            }
+        }
+    }
+
+    let mut pdf_media: Vec<_> = config.pdf_media().into_iter().collect();
+    pdf_media.sort();
+    for (named_path, media_path) in pdf_media {
+        let resolved_path = match &pdf_renderer {
+            Some(pdf_renderer) => pdf_renderer.resolve_media_path(&media_path),
+            None => media_path.clone(),
+        };
+        if !resolved_path.exists() {
+            problems.push(Problem::new(
+                format!("pdf_media.{}", named_path.to_string_lossy()),
+                format!("`{}` does not exist", media_path.to_string_lossy()),
+            ));
+        }
+    }
+
+    for (location, duration) in config.invalid_durations() {
+        problems.push(Problem::new(
+            location,
+            format!("{} is not a valid number of seconds", duration),
+        ));
+    }
+
+    if let Some(pdf_renderer) = &pdf_renderer {
+        let mut slide_settings: Vec<(&str, usize)> = config
+            .transitions()
+            .slides
+            .into_keys()
+            .map(|page_idx| ("slide_transitions", page_idx))
+            .chain(
+                config
+                    .auto_advance()
+                    .map(|auto_advance| auto_advance.slide_durations)
+                    .unwrap_or_default()
+                    .into_keys()
+                    .map(|page_idx| ("auto_advance.slide_durations", page_idx)),
+            )
+            .chain(
+                config
+                    .timer_settings()
+                    .slide_budgets
+                    .into_keys()
+                    .map(|page_idx| ("timer.slide_budgets", page_idx)),
+            )
+            .collect();
+        slide_settings.sort();
+        for (location, page_idx) in slide_settings {
+            if page_idx >= pdf_renderer.num_pages {
+                problems.push(Problem::new(
+                    format!("{}.{}", location, page_idx),
+                    format!(
+                        "slide {} does not exist, the document has {} slides (counting from 0)",
+                        page_idx, pdf_renderer.num_pages
+                    ),
+                ));
+            }
+        }
+    }
+
+    for (idx, entry) in config.video_entries().iter().enumerate() {
+        let location = format!("entries[{}]", idx);
+
+        if let Some(trigger) = entry
+            .animation
+            .as_ref()
+            .and_then(|animation| animation.trigger)
+        {
+            if VIEWER_KEYS.contains(&trigger) {
+                problems.push(Problem::new(
+                    format!("{}.animation.trigger", location),
+                    format!("`{}` is a key of the viewer already", trigger.name()),
+                ));
+            }
+        }
+
+        let media_path = match &pdf_renderer {
+            Some(pdf_renderer) => pdf_renderer.resolve_media_path(&entry.video_path),
+            None => entry.video_path.clone(),
+        };
+        let video_dim = match &entry.media_type {
+            // own kinds of overlays are not necessarily backed by a file
+            MediaType::Custom(kind) => {
+                if !overlay::is_registered(kind) {
+                    problems.push(Problem::new(
+                        format!("{}.type", location),
+                        format!("unknown type `{}`", kind),
+                    ));
+                }
+                None
+            }
+            MediaType::Video if is_image_sequence(&media_path) => {
+                match ImageSequencePlayer::probe(&media_path) {
+                    Ok(dim) => Some((dim.x, dim.y)),
+                    Err(e) => {
+                        problems.push(Problem::new(format!("{}.video_path", location), e));
+                        None
+                    }
+                }
+            }
+            _ if !media_path.exists() => {
+                problems.push(Problem::new(
+                    format!("{}.video_path", location),
+                    format!("`{}` does not exist", entry.video_path.to_string_lossy()),
+                ));
+                None
+            }
+            media_type => {
+                let probe = match media_type {
+                    MediaType::Video => VideoPlayer::probe(&media_path.to_string_lossy()),
+                    MediaType::AlphaVideo => AlphaVideoPlayer::probe(&media_path).map(|stream| {
+                        if !stream.has_alpha {
+                            problems.push(Problem::new(
+                                format!("{}.type", location),
+                                format!(
+                                    "`{}` has no alpha channel, use the type `video`",
+                                    entry.video_path.to_string_lossy()
+                                ),
+                            ));
+                        }
+                        stream.size()
+                    }),
+                    _ => ImagePlayer::probe(&media_path.to_string_lossy()),
+                };
+                match probe {
+                    Ok(dim) => Some((dim.x, dim.y)),
+                    Err(e) => {
+                        problems.push(Problem::new(
+                            format!("{}.video_path", location),
+                            format!(
+                                "`{}` could not be decoded: {}",
+                                entry.video_path.to_string_lossy(),
+                                e
+                            ),
+                        ));
+                        None
+                    }
+                }
+            }
+        };
+
+        if !entry.appearance.crop.is_valid() {
+            problems.push(Problem::new(
+                format!("{}.crop", location),
+                "cannot be negative and has to leave some of the video",
+            ));
+        }
+        // only the cropped part counts
+        let crop_size = entry.appearance.crop.uv().size();
+        let video_dim =
+            video_dim.map(|(width, height)| (width * crop_size.x, height * crop_size.y));
+
+        let Some(pdf_renderer) = &pdf_renderer else {
+            continue;
+        };
+        for &slide_num in &entry.slide_nums {
+            if slide_num >= pdf_renderer.num_pages {
+                problems.push(Problem::new(
+                    format!("{}.slide_num", location),
+                    format!(
+                        "slide {} does not exist, the document has {} slides (counting from 0)",
+                        slide_num, pdf_renderer.num_pages
+                    ),
+                ));
+                continue;
+            }
+            let Some(page_size) = pdf_renderer.page_size(slide_num) else {
+                continue;
+            };
+            problems.extend(rect_problems(entry, video_dim, page_size).into_iter().map(
+                |(field, message)| {
+                    Problem::new(
+                        format!("{}.{}", location, field),
+                        format!("{} on slide {}", message, slide_num),
+                    )
+                },
+            ));
+        }
+    }
+    problems
+}
+
+/// Checks whether the video stays inside of the slide, returns the field at fault with the problem.
+///
+/// Sizes depending on the aspect ratio of the video can only be checked if its dimensions are known.
+fn rect_problems(
+    entry: &VideoEntry,
+    video_dim: Option<(f32, f32)>,
+    page_size: (f32, f32),
+) -> Vec<(&'static str, String)> {
+    let mut problems = Vec::new();
+    let (x, y) = entry.pos.by_bbox(page_size);
+    // allow for rounding errors of the percentages
+    let eps = 1e-3;
+    if x < -eps {
+        problems.push((
+            "pos",
+            format!(
+                "video exceeds the left edge by {:.1}%",
+                -x / page_size.0 * 100.0
+            ),
+        ));
+    }
+    if y < -eps {
+        problems.push((
+            "pos",
+            format!(
+                "video exceeds the top edge by {:.1}%",
+                -y / page_size.1 * 100.0
+            ),
+        ));
+    }
+    // a position outside of the slide is wrong regardless of the size
+    let pos_outside = x >= page_size.0 - eps || y >= page_size.1 - eps;
+    if pos_outside {
+        problems.push(("pos", "video starts outside of the slide".to_owned()));
+    }
+    let (w, h) = match (entry.size, video_dim) {
+        (SizeRequest::Size(..), _) => entry.size.by_bbox((1.0, 1.0), page_size),
+        (_, Some(video_dim)) => entry.size.by_bbox(video_dim, page_size),
+        (_, None) => return problems,
+    };
+    if pos_outside {
+        return problems;
+    }
+    if x + w > page_size.0 + eps {
+        problems.push((
+            "size",
+            format!(
+                "video exceeds the right edge by {:.1}%",
+                (x + w - page_size.0) / page_size.0 * 100.0
+            ),
+        ));
+    }
+    if y + h > page_size.1 + eps {
+        problems.push((
+            "size",
+            format!(
+                "video exceeds the bottom edge by {:.1}%",
+                (y + h - page_size.1) / page_size.1 * 100.0
+            ),
+        ));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{PosRequest, SizeEntry};
+
+    fn entry(pos: (f32, f32), size: SizeRequest) -> VideoEntry {
+        VideoEntry {
+            slide_nums: vec![0],
+            video_path: PathBuf::from("video.mp4"),
+            media_type: MediaType::Video,
+            fps: None,
+            pos: PosRequest {
+                width: SizeEntry::Percent(pos.0),
+                height: SizeEntry::Percent(pos.1),
+            },
+            size,
+            appearance: Default::default(),
+            animation: None,
+        }
+    }
+
+    #[test]
+    fn rect_inside_slide() {
+        let size = SizeRequest::Size(SizeEntry::Percent(50.0), SizeEntry::Percent(50.0));
+        assert!(rect_problems(&entry((50.0, 50.0), size), None, (160.0, 90.0)).is_empty());
+    }
+
+    #[test]
+    fn rect_outside_slide() {
+        let size = SizeRequest::Size(SizeEntry::Percent(60.0), SizeEntry::Percent(10.0));
+        assert_eq!(
+            rect_problems(&entry((50.0, 0.0), size), None, (160.0, 90.0)).len(),
+            1
+        );
+        // 16:9 video at full width on a 16:9 slide, moved down by 10%
+        let size = SizeRequest::AutoHeight(SizeEntry::Percent(100.0));
+        assert_eq!(
+            rect_problems(
+                &entry((0.0, 10.0), size),
+                Some((1920.0, 1080.0)),
+                (160.0, 90.0)
+            )
+            .len(),
+            1
+        );
+        // unknown aspect ratio cannot be checked
+        assert!(rect_problems(&entry((0.0, 10.0), size), None, (160.0, 90.0)).is_empty());
+    }
+
+    #[test]
+    fn pos_outside_slide() {
+        let size = SizeRequest::Size(SizeEntry::Percent(10.0), SizeEntry::Percent(10.0));
+        let fields = |pos| {
+            rect_problems(&entry(pos, size), None, (160.0, 90.0))
+                .into_iter()
+                .map(|(field, _)| field)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fields((-5.0, 50.0)), vec!["pos"]);
+        assert_eq!(fields((50.0, -5.0)), vec!["pos"]);
+        assert_eq!(fields((120.0, 50.0)), vec!["pos"]);
+        assert_eq!(fields((95.0, 50.0)), vec!["size"]);
+    }
+}
@@ -1,8 +1,12 @@
-use egui_video::Player;
+use egui_video::{Player, PlayerState};
+
+use crate::SlideOverlay;
 
 pub struct PlayingVideo {
     path_playing: String,
     player: Player,
+    /// Whether the end has been reached, looping videos restart right away
+    finished: bool,
 }
 
 impl PlayingVideo {
@@ -10,6 +14,7 @@ impl PlayingVideo {
         Self {
             path_playing,
             player,
+            finished: false,
         }
     }
 }
@@ -23,41 +28,66 @@ impl VideoPlayer {
         Self { video: None }
     }
 
-    pub fn init(&mut self, ctx: &egui::Context, video_path: &str) {
+    /// Opens the video once to check whether it can be decoded.
+    ///
+    /// Returns the dimensions of the video on success.
+    pub fn probe(video_path: &str) -> Result<egui::Vec2, String> {
+        let player = Player::new(&egui::Context::default(), &video_path.to_owned())
+            .map_err(|e| e.to_string())?;
+        Ok(player.size)
+    }
+
+    pub fn is_path_playing(&self, video_path: &str) -> bool {
+        self.video
+            .as_ref()
+            .is_some_and(|video| video.path_playing == video_path)
+    }
+}
+
+impl SlideOverlay for VideoPlayer {
+    fn init(&mut self, ctx: &egui::Context, video_path: &str) {
         let video_path = video_path.to_owned();
         let player = Player::new(ctx, &video_path).unwrap();
         self.video = Some(PlayingVideo::new(player, video_path.to_owned()));
     }
 
-    pub fn start(&mut self) {
+    fn start(&mut self) {
         if let Some(video) = self.video.as_mut() {
             video.player.start();
         }
     }
 
-    pub fn render(&mut self, ui: &mut egui::Ui, rect: egui::Rect) -> Option<egui::Response> {
+    fn render(&mut self, ui: &mut egui::Ui, rect: egui::Rect) -> Option<egui::Response> {
         if let Some(video) = self.video.as_mut() {
+            video.finished |= matches!(
+                video.player.player_state.get(),
+                PlayerState::EndOfFile | PlayerState::Restarting
+            );
             Some(video.player.ui_at(ui, rect))
         } else {
             None
         }
     }
 
-    pub fn is_playing(&self) -> bool {
+    fn pause(&mut self) {
+        if let Some(video) = self.video.as_mut() {
+            video.player.pause();
+        }
+    }
+
+    fn is_playing(&self) -> bool {
         self.video.is_some()
     }
 
-    pub fn is_path_playing(&self, video_path: &str) -> bool {
-        self.video
-            .as_ref()
-            .is_some_and(|video| video.path_playing == video_path)
+    fn is_finished(&self) -> bool {
+        self.video.as_ref().is_some_and(|video| video.finished)
     }
 
-    pub fn destroy(&mut self) {
+    fn destroy(&mut self) {
         self.video = None
     }
 
-    pub fn size(&self) -> Option<egui::Vec2> {
+    fn size(&self) -> Option<egui::Vec2> {
         self.video.as_ref().map(|video| video.player.size)
     }
 }
@@ -0,0 +1,216 @@
+use std::time::{Duration, Instant};
+
+use egui::{Color32, ColorImage, Pos2, Rect, Stroke, TextureHandle};
+
+use crate::slides::SlidesCache;
+
+/// Largest magnification
+const MAX_ZOOM: f32 = 4.0;
+/// Selections smaller than this (in points) are taken for clicks
+const MIN_SELECTION: f32 = 10.0;
+/// How long the region has to stay the same before it is rendered at full resolution
+const RENDER_DELAY: Duration = Duration::from_millis(200);
+
+/// The whole slide, in coordinates relative to the slide.
+fn unit_rect() -> Rect {
+    Rect::from_min_max(Pos2::ZERO, egui::pos2(1.0, 1.0))
+}
+
+/// Region to zoom into for a selection relative to the slide: widened to the proportions of the
+/// slide, no smaller than allowed by the maximum magnification and moved onto the slide.
+///
+/// `None` if the region would cover the whole slide.
+fn fit_region(selection: Rect) -> Option<Rect> {
+    // relative coordinates, so the proportions of the slide are those of a square
+    let side = selection
+        .width()
+        .max(selection.height())
+        .max(1.0 / MAX_ZOOM);
+    if side >= 1.0 {
+        return None;
+    }
+    let region = Rect::from_center_size(selection.center(), egui::vec2(side, side));
+    let offset = (Pos2::ZERO - region.min).max(egui::Vec2::ZERO)
+        + (egui::pos2(1.0, 1.0) - region.max).min(egui::Vec2::ZERO);
+    Some(region.translate(offset))
+}
+
+/// Zooms into a region of the slide and pans it around.
+///
+/// While the region changes, the rendered slide is shown magnified. Once it stays the same, the
+/// region is rendered anew at the resolution of the window.
+pub struct Zoom {
+    /// Part of the slide shown, relative to the slide; `None` for the whole slide
+    region: Option<Rect>,
+    /// Where the selection of a region started
+    selection_start: Option<Pos2>,
+    /// When the region has changed last
+    changed: Instant,
+    texture: TextureHandle,
+    /// Slide, region and size the texture has been rendered for
+    rendered: Option<(usize, Rect, [usize; 2])>,
+}
+
+impl Zoom {
+    pub fn new(ctx: &egui::Context) -> Self {
+        Self {
+            region: None,
+            selection_start: None,
+            changed: Instant::now(),
+            texture: ctx.load_texture("zoomed_slide", ColorImage::example(), Default::default()),
+            rendered: None,
+        }
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.region.is_some()
+    }
+
+    /// Shows the whole slide again.
+    pub fn reset(&mut self) {
+        self.region = None;
+        self.selection_start = None;
+    }
+
+    fn set_region(&mut self, region: Option<Rect>) {
+        if region != self.region {
+            self.region = region;
+            self.changed = Instant::now();
+        }
+    }
+
+    /// Where the whole slide would be if the region is shown at `img_rect`.
+    ///
+    /// Videos and ink are placed w.r.t. this rect, clipped to `img_rect`.
+    pub fn slide_rect(&self, img_rect: Rect) -> Rect {
+        let Some(region) = self.region else {
+            return img_rect;
+        };
+        let size = img_rect.size() / region.size();
+        Rect::from_min_size(img_rect.min - region.min.to_vec2() * size, size)
+    }
+
+    /// Selects a region with ctrl + drag and zooms with ctrl + scroll (or pinch). While zoomed,
+    /// scrolling or dragging with the right or middle mouse button pans.
+    pub fn handle_input(&mut self, ui: &egui::Ui, img_rect: Rect) {
+        let slide_rect = self.slide_rect(img_rect);
+        let relative = |pos: Pos2| ((pos - slide_rect.min) / slide_rect.size()).to_pos2();
+        let (hover_pos, zoom_delta, pan) = ui.input(|i| {
+            if i.pointer.primary_pressed() && i.modifiers.command {
+                self.selection_start = i
+                    .pointer
+                    .interact_pos()
+                    .filter(|pos| img_rect.contains(*pos));
+            }
+            let mut pan = i.smooth_scroll_delta;
+            if i.pointer.button_down(egui::PointerButton::Secondary)
+                || i.pointer.button_down(egui::PointerButton::Middle)
+            {
+                pan += i.pointer.delta();
+            }
+            (i.pointer.hover_pos(), i.zoom_delta(), pan)
+        });
+
+        if let Some(start) = self.selection_start {
+            let (current, released) =
+                ui.input(|i| (i.pointer.interact_pos(), i.pointer.primary_released()));
+            let selection = Rect::from_two_pos(start, current.unwrap_or(start));
+            if released {
+                self.selection_start = None;
+                if selection.width().max(selection.height()) >= MIN_SELECTION {
+                    self.set_region(fit_region(Rect::from_two_pos(
+                        relative(selection.min),
+                        relative(selection.max),
+                    )));
+                }
+            } else {
+                ui.painter().rect_stroke(
+                    selection,
+                    0.0,
+                    Stroke::new(2.0, Color32::from_rgb(255, 200, 0)),
+                    egui::StrokeKind::Middle,
+                );
+            }
+        }
+
+        if zoom_delta != 1.0 {
+            let region = self.region.unwrap_or(unit_rect());
+            // keep the point under the cursor in place
+            let center = hover_pos
+                .filter(|pos| img_rect.contains(*pos))
+                .map(relative)
+                .unwrap_or(region.center());
+            let scale = 1.0 / zoom_delta;
+            let zoomed = Rect::from_min_max(
+                center + (region.min - center) * scale,
+                center + (region.max - center) * scale,
+            );
+            self.set_region(fit_region(zoomed));
+        }
+
+        if let Some(region) = self.region {
+            if pan != egui::Vec2::ZERO {
+                // the content follows the pointer
+                self.set_region(fit_region(region.translate(-pan / slide_rect.size())));
+            }
+        }
+    }
+
+    /// Paints the region of the slide to `img_rect`, rendering it at full resolution once it
+    /// stays the same. `texture` is the rendered slide, shown magnified in the meantime.
+    pub fn paint(
+        &mut self,
+        ui: &egui::Ui,
+        slides: &SlidesCache,
+        page_idx: usize,
+        texture: &TextureHandle,
+        img_rect: Rect,
+    ) {
+        let Some(region) = self.region else {
+            return;
+        };
+        let size = texture.size();
+        if self.rendered != Some((page_idx, region, size)) {
+            let waited = self.changed.elapsed();
+            if waited < RENDER_DELAY {
+                ui.painter()
+                    .image(texture.id(), img_rect, region, Color32::WHITE);
+                ui.ctx().request_repaint_after(RENDER_DELAY - waited);
+                return;
+            }
+            if let Some(img) = slides.render_region(page_idx, region, size) {
+                self.texture.set(img, Default::default());
+            }
+            self.rendered = Some((page_idx, region, size));
+        }
+        ui.painter()
+            .image(self.texture.id(), img_rect, unit_rect(), Color32::WHITE);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fit_selection_to_slide() {
+        let region =
+            |min: [f32; 2], max: [f32; 2]| fit_region(Rect::from_min_max(min.into(), max.into()));
+        assert_eq!(
+            region([0.25, 0.375], [0.75, 0.5]),
+            Some(Rect::from_min_max(
+                egui::pos2(0.25, 0.1875),
+                egui::pos2(0.75, 0.6875)
+            ))
+        );
+        // no further than the maximum magnification and not beyond the slide
+        assert_eq!(
+            region([0.875, 0.875], [0.9375, 0.9375]),
+            Some(Rect::from_min_max(
+                egui::pos2(0.75, 0.75),
+                egui::pos2(1.0, 1.0)
+            ))
+        );
+        assert_eq!(region([-0.1, 0.0], [1.0, 0.5]), None);
+    }
+}